@@ -0,0 +1,120 @@
+/*
+* Regenerates the planetary station tables in src/lib/data/body_speeds.rs from the local ephemeris,
+* e.g. cargo run --bin station_tables -- -e /usr/share/libswe/ephe -s 1800 -u 2100 -o src/lib/data/body_speeds.rs
+*/
+#[allow(dead_code)]
+#[path = "../lib/mod.rs"]
+mod lib;
+#[allow(dead_code)]
+#[path = "../extensions/mod.rs"]
+mod extensions;
+#[allow(dead_code)]
+#[path = "../constants.rs"]
+mod constants;
+
+extern crate libswe_sys;
+
+use std::{fs, process};
+use clap::Parser;
+use extensions::ephemeris::{init_ephemeris};
+use libswe_sys::sweconst::{OptionalFlag};
+use lib::{core::{calc_ut_key}, planet_stations::{calc_planet_stations, BodySpeed}, julian_date::{datetime_to_julian_day}, settings::graha_values::{to_unknown_body_keys_error}, utils::converters::{body_keys_str_to_keys_or, match_unknown_body_keys}};
+use constants::*;
+
+/// Planetary station table generator
+#[derive(Parser, Debug)]
+#[clap(author, version, about, long_about = None)]
+struct Args {
+    // Ephemeris path
+    #[clap(short, long, value_parser, default_value_t = SWEPH_PATH_DEFAULT.to_string() )]
+    ephemeris: String,
+    // Comma-separated body keys, the Rust format always includes all and only the default tables
+    #[clap(short, long, value_parser, default_value_t = String::from("me,ve,ma,ju,sa,ur,ne,pl") )]
+    bodies: String,
+    // First year
+    #[clap(short, long, value_parser, default_value_t = 1900 )]
+    start: i32,
+    // Last year inclusive
+    #[clap(short = 'u', long, value_parser, default_value_t = 2050 )]
+    until: i32,
+    // Output format, rust or csv
+    #[clap(short, long, value_parser, default_value_t = String::from("rust") )]
+    format: String,
+    // Output file, otherwise print to stdout
+    #[clap(short, long, value_parser, default_value_t = String::from("") )]
+    output: String,
+}
+
+/*
+* Tables referenced by src/lib/planet_stations.rs, so generated Rust output can always replace body_speeds.rs
+*/
+const TABLE_KEYS: [&str; 8] = ["me", "ve", "ma", "ju", "sa", "ur", "ne", "pl"];
+
+fn year_to_jd(year: i32) -> f64 {
+  datetime_to_julian_day(format!("{:04}-01-01T00:00:00", year).as_str())
+}
+
+/*
+* Const array in the same format as the existing tables in descending chronological order
+*/
+fn to_rust_table(key: &str, rows: &[BodySpeed]) -> String {
+  let lines: Vec<String> = rows.iter().rev().map(|row| {
+    let station_num = match row.station.as_str() {
+      "retro-start" => 0,
+      "retro-peak" => 1,
+      "retro-end" => 2,
+      _ => 3,
+    };
+    format!("    ({:.8},{},{},{})", row.jd, row.lng, row.speed, station_num)
+  }).collect();
+  format!("pub const PLANETARY_STATIONS_{}: &'static [(f64, f64,f64, u8)] = &[\n{}\n];\n", key.to_uppercase(), lines.join(",\n"))
+}
+
+fn to_csv_rows(key: &str, rows: &[BodySpeed]) -> String {
+  rows.iter().map(|row| format!("{},{:.8},{},{},{}\n", key, row.jd, row.lng, row.speed, row.station)).collect()
+}
+
+fn main() {
+  let args = Args::parse();
+  init_ephemeris(args.ephemeris.as_str());
+  let start_jd = year_to_jd(args.start);
+  let end_jd = year_to_jd(args.until + 1);
+  if let Some(error) = to_unknown_body_keys_error(&match_unknown_body_keys(args.bodies.as_str())) {
+    eprintln!("{}", error);
+    process::exit(1);
+  }
+  let is_csv = args.format.to_lowercase() == "csv";
+  let keys: Vec<String> = if is_csv { body_keys_str_to_keys_or(args.bodies.clone(), TABLE_KEYS.to_vec()) } else { TABLE_KEYS.iter().map(|k| k.to_string()).collect() };
+  if !is_csv {
+    let other_keys: Vec<String> = body_keys_str_to_keys_or(args.bodies.clone(), TABLE_KEYS.to_vec()).into_iter().filter(|k| !TABLE_KEYS.contains(&k.as_str())).collect();
+    if !other_keys.is_empty() {
+      eprintln!("Rust tables are only read for {}, use -f csv for {}", TABLE_KEYS.join(","), other_keys.join(","));
+      process::exit(1);
+    }
+  }
+  for key in keys.iter() {
+    for jd in [start_jd, end_jd] {
+      let result = calc_ut_key(jd, key.as_str(), OptionalFlag::Speed as i32);
+      if result.status < 0 {
+        eprintln!("{}: {}", key, result.serr);
+        process::exit(1);
+      }
+    }
+  }
+  let mut content = if is_csv { "key,jd,lng,speed,station\n".to_string() } else { "pub const PLANETARY_STATIONS_EA: &'static [(f64, f64,f64, u8)] =  &[];\n".to_string() };
+  for key in keys.iter().map(|k| k.as_str()) {
+    let rows = calc_planet_stations(key, start_jd, end_jd);
+    eprintln!("{}: {} stations", key, rows.len());
+    if is_csv {
+      content.push_str(to_csv_rows(key, &rows).as_str());
+    } else {
+      content.push('\n');
+      content.push_str(to_rust_table(key, &rows).as_str());
+    }
+  }
+  if !args.output.is_empty() {
+    fs::write(args.output.as_str(), content).expect("Unable to write the station tables");
+  } else {
+    print!("{}", content);
+  }
+}
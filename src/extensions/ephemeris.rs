@@ -0,0 +1,125 @@
+use std::cell::{Cell, RefCell};
+use std::sync::{Mutex, MutexGuard};
+use libswe_sys::swerust::{handler_swe02::{set_ephe_path}};
+use super::swe::{set_topo, set_sid_mode};
+
+/*
+* Shared Swiss Ephemeris settings. The C library keeps the ephemeris path, topocentric position and
+* sidereal mode in global (or per-thread) state, so all calculations run while holding this lock
+*/
+pub struct EphemerisState {
+  path: String,
+}
+
+static EPHEMERIS: Mutex<EphemerisState> = Mutex::new(EphemerisState { path: String::new() });
+
+thread_local! {
+  // number of nested locks held by the current thread
+  static LOCK_DEPTH: Cell<u32> = Cell::new(0);
+  // ephemeris path last applied on the current thread, as the library may be compiled with thread-local storage
+  static APPLIED_PATH: RefCell<Option<String>> = RefCell::new(None);
+}
+
+/*
+* Exclusive access to the Swiss Ephemeris until dropped. Nested locks on the same thread are allowed
+* so that calculations may lock themselves while called within a locked request
+*/
+pub struct EphemerisLock {
+  _guard: Option<MutexGuard<'static, EphemerisState>>,
+}
+
+impl Drop for EphemerisLock {
+  fn drop(&mut self) {
+    LOCK_DEPTH.with(|depth| depth.set(depth.get() - 1));
+  }
+}
+
+pub fn lock_ephemeris() -> EphemerisLock {
+  let depth = LOCK_DEPTH.with(|depth| depth.get());
+  let guard = if depth > 0 {
+    None
+  } else {
+    let state = EPHEMERIS.lock().unwrap_or_else(|e| e.into_inner());
+    APPLIED_PATH.with(|applied| {
+      let mut applied_path = applied.borrow_mut();
+      if applied_path.as_ref() != Some(&state.path) {
+        set_ephe_path(state.path.as_str());
+        *applied_path = Some(state.path.clone());
+      }
+    });
+    Some(state)
+  };
+  LOCK_DEPTH.with(|depth| depth.set(depth.get() + 1));
+  EphemerisLock { _guard: guard }
+}
+
+/*
+* Set the ephemeris path once on startup. Each thread applies it on its first lock
+*/
+pub fn init_ephemeris(path: &str) {
+  EPHEMERIS.lock().unwrap_or_else(|e| e.into_inner()).path = path.to_string();
+  with_ephemeris(|| set_sid_mode(0));
+}
+
+pub fn with_ephemeris<T>(calc: impl FnOnce() -> T) -> T {
+  let _lock = lock_ephemeris();
+  calc()
+}
+
+/*
+* Apply the topocentric position for the calculation only
+*/
+pub fn with_topo<T>(lat: f64, lng: f64, alt: f64, calc: impl FnOnce() -> T) -> T {
+  with_ephemeris(|| {
+    set_topo(lat, lng, alt);
+    calc()
+  })
+}
+
+/*
+* Apply the sidereal mode for the calculation only and restore the tropical default afterwards
+*/
+pub fn with_sid_mode<T>(mode: i32, calc: impl FnOnce() -> T) -> T {
+  with_ephemeris(|| {
+    set_sid_mode(mode);
+    let result = calc();
+    set_sid_mode(0);
+    result
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::thread;
+  use super::super::super::lib::{core::{calc_body_jd_topo, get_ayanamsha_value}, models::geo_pos::GeoPos};
+
+  fn calc_sample(index: usize) -> (f64, f64, f64) {
+    let jd = 2451545f64 + index as f64 * 0.25f64;
+    let geo = GeoPos::new(-60f64 + index as f64 * 15f64, -170f64 + index as f64 * 40f64, index as f64 * 250f64);
+    let aya_keys = ["lahiri", "krishnamurti", "raman", "yukteshwar"];
+    let pos = calc_body_jd_topo(jd, "mo", geo, 0f64);
+    (pos.lng, pos.lat, get_ayanamsha_value(jd, aya_keys[index % aya_keys.len()]))
+  }
+
+  #[test]
+  fn allows_nested_locks() {
+    let value = with_ephemeris(|| with_topo(51.5, -0.1, 0f64, || with_ephemeris(|| 1)));
+    assert_eq!(value, 1);
+  }
+
+  #[test]
+  fn parallel_results_match_serial_results() {
+    let num_samples = 8;
+    let serial: Vec<(f64, f64, f64)> = (0..num_samples).map(|index| calc_sample(index)).collect();
+    let parallel: Vec<Vec<(f64, f64, f64)>> = thread::scope(|scope| {
+      let handles: Vec<_> = (0..num_samples).map(|index| scope.spawn(move || (0..25).map(|_| calc_sample(index)).collect::<Vec<(f64, f64, f64)>>())).collect();
+      handles.into_iter().map(|handle| handle.join().unwrap()).collect()
+    });
+    for (index, results) in parallel.iter().enumerate() {
+      for result in results {
+        assert_eq!(*result, serial[index]);
+      }
+    }
+  }
+}
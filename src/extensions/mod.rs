@@ -1 +1,2 @@
-pub mod swe;
\ No newline at end of file
+pub mod swe;
+pub mod ephemeris;
\ No newline at end of file
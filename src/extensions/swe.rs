@@ -3,7 +3,10 @@ use std::os::raw::{c_char, c_double, c_int};
 //use std::{thread, time};
 use serde::{Serialize, Deserialize};
 use libswe_sys::sweconst::{Bodies};
-use super::super::lib::{settings::ayanamshas::*};
+use libswe_sys::swerust::{handler_swe03::{CalcUtResult}};
+use std::fmt;
+use super::super::lib::{settings::ayanamshas::*, models::geo_pos::GeoPos};
+use super::ephemeris::{with_sid_mode};
 
 #[derive(Serialize, Deserialize, Debug, Copy, Clone)]
 pub struct AltitudeSet {
@@ -20,7 +23,7 @@ enum BodyAltitudes {
 #[link(name = "swe")]
 extern "C" {
   
-  pub fn swe_rise_trans(
+  pub fn swe_rise_trans_true_hor(
       tjd_ut: c_double,
       ipl: c_int,
       starname: *mut [c_char; 0],
@@ -29,9 +32,10 @@ extern "C" {
       geopos: *mut [c_double; 3],
       atpress: c_double,
       attemp: c_double,
+      horhgt: c_double,
       tret: *mut [c_double; 3],
       serr: *mut c_char
-  );
+  ) -> c_int;
 
   /*
    double tjd_ut,
@@ -43,6 +47,28 @@ extern "C" {
     double *xaz) 
   */
 
+  pub fn swe_calc_ut(
+      tjd_ut: c_double,
+      ipl: c_int,
+      iflag: c_int,
+      xx: *mut [c_double; 6],
+      serr: *mut c_char
+  ) -> c_int;
+
+  pub fn swe_fixstar2_ut(
+      star: *mut c_char,
+      tjd_ut: c_double,
+      iflag: c_int,
+      xx: *mut [c_double; 6],
+      serr: *mut c_char
+  ) -> c_int;
+
+  pub fn swe_fixstar2_mag(
+      star: *mut c_char,
+      mag: *mut c_double,
+      serr: *mut c_char
+  ) -> c_int;
+
   pub fn swe_azalt(
       tjd_ut: c_double,
       iflag: c_int,
@@ -72,54 +98,166 @@ extern "C" {
   // swe_set_sid_mode(sidModeNum, 0, 0);
   pub fn swe_set_sid_mode(sid_mode: i32, t9: f64, ayan_t0: f64);
 
+  pub fn swe_sidtime(tjd_ut: c_double) -> c_double;
+
+  /*
+    double tjd_start,
+    int32 ifl,
+    int32 ifltype,
+    double *tret,
+    AS_BOOL backward,
+    char *serr
+  */
+  pub fn swe_sol_eclipse_when_glob(
+      tjd_start: c_double,
+      ifl: c_int,
+      ifltype: c_int,
+      tret: *mut [c_double; 10],
+      backward: c_int,
+      serr: *mut c_char
+  ) -> c_int;
+
+  pub fn swe_sol_eclipse_when_loc(
+      tjd_start: c_double,
+      ifl: c_int,
+      geopos: *mut [c_double; 3],
+      tret: *mut [c_double; 10],
+      attr: *mut [c_double; 20],
+      backward: c_int,
+      serr: *mut c_char
+  ) -> c_int;
+
+  pub fn swe_lun_eclipse_when(
+      tjd_start: c_double,
+      ifl: c_int,
+      ifltype: c_int,
+      tret: *mut [c_double; 10],
+      backward: c_int,
+      serr: *mut c_char
+  ) -> c_int;
+
 
   /* // convert ecliptic to equatorial
   pub fn swe_cotrans(xin: *mut [c_double; 3], xout: *mut [c_double; 3], eps: c_double);
  */
 }
 
-/**
- * May trigger freeing of unallocated memory
- * Call via sleep
- */
-pub fn rise_trans_raw(tjd_ut: f64, ipl: Bodies, lat: f64, lng: f64, iflag: i32) -> [f64; 3] {
-  let mut serr = [0; 255];
-  let geopos = &mut [lng, lat, 0f64];
-  let star_ref = &mut [];
-  let result = unsafe {
-    let p_xx: &mut [f64; 3] = &mut [0f64, 0f64, 0f64];
-    std::ptr::drop_in_place(p_xx);
-    //let bx = Box::new(*p_xx);
-    let p_serr = serr.as_mut_ptr();
-    swe_rise_trans(
-        tjd_ut,
-        ipl as i32,
-        star_ref,
-        0,
-        iflag,
-        geopos,
-        0f64,
-        0f64,
-        p_xx,
-        p_serr,
-    );
-    *p_xx
-  };
-  result.to_owned()
+/*
+  Null-terminated star name buffer, which the Swiss Ephemeris overwrites with the matched name and nomenclature
+*/
+fn to_star_buffer(name: &str) -> [c_char; 256] {
+  let mut star = [0 as c_char; 256];
+  for (i, b) in name.bytes().take(255).enumerate() {
+    star[i] = b as c_char;
+  }
+  star
+}
+
+fn from_star_buffer(star: &[c_char; 256]) -> String {
+  let bytes: Vec<u8> = star.iter().take_while(|c| **c != 0).map(|c| *c as u8).collect();
+  String::from_utf8_lossy(&bytes).to_string()
+}
+
+fn from_serr(serr: &[c_char; 255]) -> String {
+  let bytes: Vec<u8> = serr.iter().take_while(|c| **c != 0).map(|c| *c as u8).collect();
+  String::from_utf8_lossy(&bytes).to_string()
+}
+
+/*
+  Atmospheric pressure in hPa (mbar) and temperature in ºC for refraction at the horizon.
+  With zero pressure the Swiss Ephemeris estimates it from the observer's altitude.
+  The horizon is the altitude in degrees at which bodies rise and set, e.g. -6 for civil twilight.
+  Without refraction, rise and set refer to the disc centre as in Indian astronomy, otherwise to the upper limb
+*/
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq)]
+pub struct Atmosphere {
+  pub pressure: f64,
+  pub temperature: f64,
+  pub horizon: f64,
+  pub refraction: bool,
 }
 
-/* pub fn rise_trans_value(tjd_ut: f64, ipl: Bodies, lat: f64, lng: f64, iflag: i32) -> f64 {
-  rise_trans_raw(tjd_ut, ipl, lat, lng, iflag)[0]
+impl Atmosphere {
+  pub fn new(pressure: f64, temperature: f64, horizon: f64, refraction: bool) -> Atmosphere {
+    Atmosphere { pressure, temperature, horizon, refraction }
+  }
+
+  pub fn with_horizon(&self, horizon: f64) -> Atmosphere {
+    Atmosphere { horizon, ..*self }
+  }
 }
 
-pub fn rise_trans(tjd_ut: f64, ipl: Bodies, lat: f64, lng: f64, iflag: i32) -> f64 {
-  let ten_millis = time::Duration::from_millis(10);
-  thread::sleep(ten_millis);
-  rise_trans_value(tjd_ut, ipl, lat, lng, iflag)
-} */
+impl Default for Atmosphere {
+  fn default() -> Atmosphere {
+    Atmosphere { pressure: 0f64, temperature: 0f64, horizon: 0f64, refraction: false }
+  }
+}
 
-pub fn rise_trans(tjd_ut: f64, ipl: Bodies, lat: f64, lng: f64, iflag: i32) -> f64 {
-  rise_trans_raw(tjd_ut, ipl, lat, lng, iflag)[0]
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq)]
+pub struct RiseTransEvent {
+  pub jd: f64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SweError {
+  // the body is circumpolar or never rises at the location, so there is no rise or set
+  Circumpolar,
+  // the calculation failed with the Swiss Ephemeris error message
+  Failed(String),
+}
+
+impl fmt::Display for SweError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      SweError::Circumpolar => write!(f, "circumpolar or never rises"),
+      SweError::Failed(message) => write!(f, "{}", message),
+    }
+  }
+}
+
+/*
+  Wrapper for swe_rise_trans_true_hor. Returns the time of the next rise, set or transit after tjd_ut as set in iflag
+  for the observer's coordinates and altitude with the horizon altitude and atmospheric conditions
+*/
+pub fn rise_trans(tjd_ut: f64, ipl: Bodies, geo: GeoPos, atmosphere: Atmosphere, iflag: i32) -> Result<RiseTransEvent, SweError> {
+  rise_trans_named(tjd_ut, ipl as i32, "", geo, atmosphere, iflag)
+}
+
+/*
+  Rise, set and transit times of a fixed star matched by name from sefstars.txt
+*/
+pub fn rise_trans_star(tjd_ut: f64, star_name: &str, geo: GeoPos, atmosphere: Atmosphere, iflag: i32) -> Result<RiseTransEvent, SweError> {
+  rise_trans_named(tjd_ut, 0, star_name, geo, atmosphere, iflag)
+}
+
+fn rise_trans_named(tjd_ut: f64, ipl: i32, star_name: &str, geo: GeoPos, atmosphere: Atmosphere, iflag: i32) -> Result<RiseTransEvent, SweError> {
+  let mut serr = [0 as c_char; 255];
+  let mut geopos = [geo.lng, geo.lat, geo.alt];
+  let mut tret = [0f64; 3];
+  let mut star = to_star_buffer(star_name);
+  let flag = unsafe {
+    swe_rise_trans_true_hor(
+        tjd_ut,
+        ipl,
+        star.as_mut_ptr() as *mut [c_char; 0],
+        0,
+        iflag,
+        &mut geopos,
+        atmosphere.pressure,
+        atmosphere.temperature,
+        atmosphere.horizon,
+        &mut tret,
+        serr.as_mut_ptr(),
+    )
+  };
+  match flag {
+    0 => Ok(RiseTransEvent { jd: tret[0] }),
+    -2 => Err(SweError::Circumpolar),
+    _ => {
+      let bytes: Vec<u8> = serr.iter().take_while(|c| **c != 0).map(|c| *c as u8).collect();
+      Err(SweError::Failed(String::from_utf8_lossy(&bytes).to_string()))
+    }
+  }
 }
 
 /*
@@ -128,8 +266,15 @@ pub fn rise_trans(tjd_ut: f64, ipl: Bodies, lat: f64, lng: f64, iflag: i32) -> f
   is_equal: if true 
 */
 pub fn azalt(tjd_ut: f64, is_equal: bool, geo_lat: f64, geo_lng: f64, lng: f64, lat: f64) -> AltitudeSet {
+  azalt_atmosphere(tjd_ut, is_equal, GeoPos::simple(geo_lat, geo_lng), Atmosphere::default(), lng, lat)
+}
+
+/*
+  As above with the observer's altitude, pressure and temperature for the apparent (refracted) altitude
+*/
+pub fn azalt_atmosphere(tjd_ut: f64, is_equal: bool, geo: GeoPos, atmosphere: Atmosphere, lng: f64, lat: f64) -> AltitudeSet {
   let iflag = if is_equal { BodyAltitudes::EquToHor } else { BodyAltitudes::EclToHor } as i32;
-  let geopos = &mut [geo_lng, geo_lat, 0f64];
+  let geopos = &mut [geo.lng, geo.lat, geo.alt];
   let result = unsafe {
       let p_xin = &mut [lng, lat];
       let p_xaz = &mut [0f64, 0f64, 0f64];
@@ -137,8 +282,8 @@ pub fn azalt(tjd_ut: f64, is_equal: bool, geo_lat: f64, geo_lng: f64, lng: f64,
           tjd_ut,
           iflag,
           geopos,
-          0f64,
-          0f64,
+          atmosphere.pressure,
+          atmosphere.temperature,
           p_xin,
           p_xaz,
       );
@@ -151,11 +296,155 @@ pub fn azalt(tjd_ut: f64, is_equal: bool, geo_lat: f64, geo_lng: f64, lng: f64,
   }
 }
 
+/*
+  Wrapper for swe_sol_eclipse_when_glob. Returns the eclipse type flags, negative on error, with
+  the times of maximum, local noon, begin, end, totality begin and end and center line begin and end
+*/
+pub fn sol_eclipse_when_glob(tjd_start: f64, ecl_type: i32, backward: bool) -> (i32, [f64; 10]) {
+  let mut serr = [0; 255];
+  let mut tret = [0f64; 10];
+  let flag = unsafe {
+    let p_serr = serr.as_mut_ptr();
+    swe_sol_eclipse_when_glob(
+        tjd_start,
+        2i32, // SEFLG_SWIEPH
+        ecl_type,
+        &mut tret,
+        backward as i32,
+        p_serr
+    )
+  };
+  (flag, tret)
+}
+
+/*
+  Wrapper for swe_sol_eclipse_when_loc. Returns the next eclipse visible at the location with its flags,
+  the times of maximum and the 1st to 4th contacts and attributes such as magnitude, obscuration and the solar altitude
+*/
+pub fn sol_eclipse_when_loc(tjd_start: f64, lat: f64, lng: f64, alt: f64, backward: bool) -> (i32, [f64; 10], [f64; 20]) {
+  let mut serr = [0; 255];
+  let mut tret = [0f64; 10];
+  let mut attr = [0f64; 20];
+  let geopos = &mut [lng, lat, alt];
+  let flag = unsafe {
+    let p_serr = serr.as_mut_ptr();
+    swe_sol_eclipse_when_loc(
+        tjd_start,
+        2i32, // SEFLG_SWIEPH
+        geopos,
+        &mut tret,
+        &mut attr,
+        backward as i32,
+        p_serr
+    )
+  };
+  (flag, tret, attr)
+}
+
+/*
+  Wrapper for swe_lun_eclipse_when. Returns the eclipse type flags, negative on error, with the times of maximum,
+  partial phase begin and end, totality begin and end and penumbral phase begin and end
+*/
+pub fn lun_eclipse_when(tjd_start: f64, ecl_type: i32, backward: bool) -> (i32, [f64; 10]) {
+  let mut serr = [0; 255];
+  let mut tret = [0f64; 10];
+  let flag = unsafe {
+    let p_serr = serr.as_mut_ptr();
+    swe_lun_eclipse_when(
+        tjd_start,
+        2i32, // SEFLG_SWIEPH
+        ecl_type,
+        &mut tret,
+        backward as i32,
+        p_serr
+    )
+  };
+  (flag, tret)
+}
+
+/*
+  Wrapper for swe_calc_ut by body number, for bodies without a Bodies variant such as numbered asteroids,
+  i.e. 10000 + the MPC number, which require the matching asteroid files in the ephemeris path
+*/
+pub fn calc_ut_num(tjd_ut: f64, ipl: i32, iflag: i32) -> CalcUtResult {
+  let mut serr = [0 as c_char; 255];
+  let mut xx = [0f64; 6];
+  let status = unsafe {
+    let p_serr = serr.as_mut_ptr();
+    swe_calc_ut(
+        tjd_ut,
+        ipl,
+        iflag,
+        &mut xx,
+        p_serr
+    )
+  };
+  let bytes: Vec<u8> = serr.iter().take_while(|c| **c != 0).map(|c| *c as u8).collect();
+  CalcUtResult {
+    longitude: xx[0],
+    latitude: xx[1],
+    distance_au: xx[2],
+    speed_longitude: xx[3],
+    speed_latitude: xx[4],
+    speed_distance_au: xx[5],
+    status,
+    serr: String::from_utf8_lossy(&bytes).to_string(),
+  }
+}
+
+/*
+  Wrapper for swe_fixstar2_ut with precession and proper motion. Returns the matched star name as name,nomenclature
+  and longitude, latitude, distance and their speeds, or right ascension and declination with SEFLG_EQUATORIAL,
+  or the Swiss Ephemeris error, e.g. if the star or sefstars.txt is not found
+*/
+pub fn fixstar2_ut(star_name: &str, tjd_ut: f64, iflag: i32) -> Result<(String, [f64; 6]), SweError> {
+  let mut serr = [0 as c_char; 255];
+  let mut xx = [0f64; 6];
+  let mut star = to_star_buffer(star_name);
+  let flag = unsafe {
+    let p_serr = serr.as_mut_ptr();
+    swe_fixstar2_ut(
+        star.as_mut_ptr(),
+        tjd_ut,
+        iflag,
+        &mut xx,
+        p_serr
+    )
+  };
+  if flag < 0 {
+    Err(SweError::Failed(from_serr(&serr)))
+  } else {
+    Ok((from_star_buffer(&star), xx))
+  }
+}
+
+/*
+  Wrapper for swe_fixstar2_mag. Returns the visual magnitude or the Swiss Ephemeris error
+*/
+pub fn fixstar2_mag(star_name: &str) -> Result<f64, SweError> {
+  let mut serr = [0 as c_char; 255];
+  let mut mag = 0f64;
+  let mut star = to_star_buffer(star_name);
+  let flag = unsafe {
+    let p_serr = serr.as_mut_ptr();
+    swe_fixstar2_mag(
+        star.as_mut_ptr(),
+        &mut mag,
+        p_serr
+    )
+  };
+  if flag < 0 {
+    Err(SweError::Failed(from_serr(&serr)))
+  } else {
+    Ok(mag)
+  }
+}
+
 pub fn get_ayanamsha(tjd_ut: f64, mode: Ayanamsha) -> f64 {
   let mut daya: [f64; 1] = [0.0; 1];
   let mut serr = [0; 255];
-  set_sid_mode(mode.as_i32());
-  let result = unsafe {
+  // the sidereal mode only applies while the ephemeris is locked
+  with_sid_mode(mode.as_i32(), || unsafe {
       let p_daya = daya.as_mut_ptr();
       let p_serr = serr.as_mut_ptr();
       let status = swe_get_ayanamsa_ex_ut(
@@ -165,9 +454,7 @@ pub fn get_ayanamsha(tjd_ut: f64, mode: Ayanamsha) -> f64 {
           p_serr
       );
       status
-  };
-  //set_sid_mode(0);
-  result
+  })
 }
 
 pub fn set_topo(lat: f64, lng: f64, alt: f64) {
@@ -182,4 +469,11 @@ pub fn set_sid_mode(iflag: i32) {
   }
 }
 
-
+/*
+  Greenwich apparent sidereal time in hours
+*/
+pub fn sidtime(tjd_ut: f64) -> f64 {
+  unsafe {
+    swe_sidtime(tjd_ut)
+  }
+}
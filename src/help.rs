@@ -20,13 +20,15 @@ pub fn endpoint_help() -> HashMap<String, HashMap<String,String>> {
   
   help.insert("/positions".to_string(), info_map(
     vec![
-      ("description", "Longitudes of referenced celestial bodies and the ascendant"),
+      ("description", "Longitudes of referenced celestial bodies and the ascendant with the current moon phase"),
       ( "dt", "Date"),
       ("loc", "lat,lng(,alt) coordinates"),
       ("bodies", "comma-separated list of required bodies, all or core"),
       ("topo", "0 = geocentric, 1 topocentric"),
       ("eq", "0 = ecliptic, 1 equatorial"),
       ("iso", "0 julian days (transition times), 1 ISO UTC datetime strings"),
+      ("meta", "1: show sign, nakshatra, pada and their lords for each body and the ascendant"),
+      ("nak", "number of nakshatras, 27 (default) or 28 with Abhijit"),
     ]
   ));
   help.insert("/chart-data".to_string(), info_map(
@@ -35,7 +37,7 @@ pub fn endpoint_help() -> HashMap<String, HashMap<String,String>> {
       ("loc", "lat,lng(,alt) coordinates, e.g. &loc=45.336,13.278,50 or just &loc=45.336,13.278"),
       ("bodies", "comma-separated list of required bodies, all or core"),
       ("topo", "0: geocentric, 1: topocentric"),
-      ("eq", "0: ecliptic only, 1 equatorial only, 2: show equatorial and ecliptic, 3: show azimuth and altitide. 4: Also show other planetary phenomena"),
+      ("eq", "0: ecliptic only, 1 equatorial only, 2: show equatorial and ecliptic, 3: show azimuth and altitide with apparent altitude, hour angle, parallactic angle and airmass in topocentric mode. 4: Also show other planetary phenomena"),
       ("it", "1: show Indian time units and progression from sunrise to sunrise , 0: do not show Indian time"),
       ("retro", "1: show retrograde and peak stations of the main planets, 0: do not show planet stations."),
       ("ct", "include transits for the referenced bodies"),
@@ -48,6 +50,28 @@ pub fn endpoint_help() -> HashMap<String, HashMap<String,String>> {
       ("p2start", "Explcit start year for p2 data (alternative to above"),
       ("p2py", "Number of p2 sample per year, default 2."),
       ("p2bodies", "Bodies to captured for P2. These never include Uranus, Neptune, Pluto or asteroid. Narrow range to limit the payload"),
+      ("aspects", "1 or major: major aspects, all: major and minor aspects, or comma-separated aspect keys, e.g. conjunction,square"),
+      ("orbs", "comma-separated orb overrides per body in degrees, e.g. su:12,mo:10"),
+      ("dasha", "0: no dasha periods (default), 1: vimshottari mahadashas, 2: with antardashas, 3: with pratyantardashas"),
+      ("yt", "year type for dasha periods and P2 data: tropical (default), sidereal or anomalistic"),
+      ("meta", "1: show sign, nakshatra, pada and their lords for each body and the ascendant"),
+      ("nak", "number of nakshatras, 27 (default) or 28 with Abhijit"),
+      ("vargas", "all or comma-separated divisional chart numbers, e.g. 9,10,60, showing sidereal varga signs for each body and the ascendant"),
+      ("stars", "1 or all: show the default fixed stars, or comma-separated star names, e.g. Regulus,Spica, with rise, set and culmination times if ct is 1"),
+      ("lots", "1 or all: show all Hermetic lots (fortune, spirit, eros, necessity, courage, victory, nemesis) with sign and house, or comma-separated lot keys. Custom formulas may be posted as JSON via lotFormulas"),
+    ]
+  ));
+  help.insert("/aspects".to_string(), info_map(
+    vec![
+      ("description", "Aspects between bodies, the ascendant and MC of two charts (synastry)"),
+      ("dt", "date of the first chart"),
+      ("loc", "lat,lng(,alt) coordinates of the first chart"),
+      ("dt2", "date of the second chart"),
+      ("loc2", "lat,lng(,alt) coordinates of the second chart"),
+      ("bodies", "comma-separated list of required bodies, all or core"),
+      ("topo", "0 = geocentric, 1 topocentric"),
+      ("aspects", "major (default), all or comma-separated aspect keys"),
+      ("orbs", "comma-separated orb overrides per body in degrees, e.g. su:12,mo:10"),
     ]
   ));
   help.insert("/progress".to_string(), info_map(
@@ -70,14 +94,23 @@ pub fn endpoint_help() -> HashMap<String, HashMap<String,String>> {
       ("bodies", "comma-separated list of required bodies, all or core"),
       ("iso", "0 show all times as julian days, 1 show transitions times as ISO UTC datetime strings"),
       ("days", "Number of days from the start date"),
+      ("pres", "atmospheric pressure in hPa (mbar) for refraction, 0 (default) estimates it from the altitude in loc"),
+      ("temp", "temperature in ºC for refraction, default 0"),
+      ("hor", "horizon altitude in degrees at which bodies rise and set, default 0, e.g. -6, -12 or -18 for civil, nautical or astronomical twilight"),
+      ("refr", "0 = rise and set of the disc centre without refraction (default), 1 = upper limb with refraction"),
     ]
   ));
   help.insert("/sun-transitions".to_string(), info_map(
     vec![
+      ("description", "Daily sun transitions with civil, nautical and astronomical dawn and dusk and golden and blue hours"),
       ("dateref", "current date-time"),
       ("loc", "current lat,lng(,alt) coordinates"),
       ("days", "number of days worth of transitions, default 28, e.g. 366 will return a whole year"),
-      ("iso", "0 = show julian days (default), 1 = show ISO datetime UTC")
+      ("iso", "0 = show julian days (default), 1 = show ISO datetime UTC"),
+      ("pres", "atmospheric pressure in hPa (mbar) for refraction, 0 (default) estimates it from the altitude in loc"),
+      ("temp", "temperature in ºC for refraction, default 0"),
+      ("hor", "horizon altitude in degrees at which bodies rise and set, default 0, e.g. -6, -12 or -18 for civil, nautical or astronomical twilight"),
+      ("refr", "0 = rise and set of the disc centre without refraction (default), 1 = upper limb with refraction"),
     ]
   ));
   help.insert("/transposed-transitions".to_string(), info_map(
@@ -91,6 +124,224 @@ pub fn endpoint_help() -> HashMap<String, HashMap<String,String>> {
     ]
   ));
 
+  help.insert("/transposed-transitions-chart (POST)".to_string(), info_map(
+    vec![
+      ("dt", "current date-time"),
+      ("loc", "current lat,lng(,alt) coordinates"),
+      ("days", "Number of days from the current date"),
+      ("bodies", "array of pre-calculated body positions with key, lng, lat and lngSpeed"),
+      ("lngs", "array of key/value longitudes, where latitude and speed are unknown"),
+      ("positions", "object of longitudes keyed by body, as returned by /positions"),
+      ("iso", "0 = show julian days (default), 1 = show ISO datetime UTC"),
+    ]
+  ));
+
+  help.insert("/transit-aspects".to_string(), info_map(
+    vec![
+      ("description", "Exact times when transiting bodies form aspects to natal positions, including retrograde passes. Natal positions may also be posted as JSON with bodies, lngs or positions, transitBodies, aspects, days, years and iso"),
+      ("dt", "start date, default: current date"),
+      ("days", "number of days from the start date, default 366"),
+      ("years", "number of years from the start date, overrides days, max 100"),
+      ("bodies", "comma-separated list of transiting bodies, default ju,sa,ur,ne,pl"),
+      ("dt2", "date of the natal chart"),
+      ("loc2", "coordinates of the natal chart, required for the ascendant and MC"),
+      ("bodies2", "comma-separated list of natal bodies, default su,mo,me,ve,ma,ju,sa. The ascendant and MC are always included"),
+      ("aspects", "major (default), all or comma-separated aspect keys"),
+      ("iso", "0 = show julian days (default), 1 = show ISO datetime UTC"),
+    ]
+  ));
+
+  help.insert("/dasha".to_string(), info_map(
+    vec![
+      ("description", "Vimshottari dasha periods from the sidereal Moon longitude at birth"),
+      ("dt", "date of birth"),
+      ("aya", "ayanamsha key, default true_citra"),
+      ("yt", "year type: tropical (default), sidereal or anomalistic"),
+      ("dasha", "1: mahadashas, 2: with antardashas (default), 3: with pratyantardashas"),
+      ("iso", "0 = show julian days (default), 1 = also show ISO datetime UTC"),
+    ]
+  ));
+
+  help.insert("/panchanga".to_string(), info_map(
+    vec![
+      ("description", "Tithi, nakshatra, yoga, karana and vara for the sunrise-to-sunrise day at a location, each with start and end times"),
+      ("dt", "date"),
+      ("loc", "lat,lng(,alt) coordinates, required for sunrise"),
+      ("aya", "ayanamsha key for the nakshatra and yoga, default true_citra"),
+      ("tzs", "timezone offset in seconds for the week day, default local solar time"),
+      ("iso", "0 = show julian days (default), 1 = also show ISO datetime UTC"),
+      ("pres, temp, hor, refr", "atmospheric pressure, temperature, horizon altitude and refraction for sunrise as in /transitions"),
+    ]
+  ));
+
+  help.insert("/eclipses".to_string(), info_map(
+    vec![
+      ("description", "Solar and lunar eclipses over a time span with type, time of maximum, contact times and the previous eclipse of each kind"),
+      ("dt", "start date"),
+      ("days", "number of days to search, default 366"),
+      ("years", "number of years to search, overrides days, max 100"),
+      ("ecl", "solar, lunar or both (default)"),
+      ("loc", "optional lat,lng(,alt) coordinates for local visibility, magnitude and contact times"),
+      ("iso", "0 = show julian days (default), 1 = show ISO datetime UTC"),
+    ]
+  ));
+
+  help.insert("/moon-phases".to_string(), info_map(
+    vec![
+      ("description", "Exact new moon, first quarter, full moon and last quarter times over a time span with the current moon phase"),
+      ("dt", "start date"),
+      ("days", "number of days to search, default 366"),
+      ("years", "number of years to search, overrides days, max 100"),
+      ("iso", "0 = show julian days (default), 1 = also show ISO datetime UTC"),
+    ]
+  ));
+
+  help.insert("/ingresses".to_string(), info_map(
+    vec![
+      ("description", "Exact times when bodies enter signs, nakshatras or cross custom degrees over a time span, including retrograde re-entries"),
+      ("dt", "start date"),
+      ("days", "number of days to search, default 366"),
+      ("years", "number of years to search, overrides days, max 100"),
+      ("bodies", "comma-separated list of body keys, default su,mo,me,ve,ma,ju,sa"),
+      ("ingress", "sign (default), nakshatra or comma-separated degrees, e.g. 15,45"),
+      ("sid", "0: tropical (default), 1: sidereal"),
+      ("aya", "ayanamsha key for sidereal longitudes, default true_citra"),
+      ("iso", "0 = show julian days (default), 1 = also show ISO datetime UTC"),
+    ]
+  ));
+
+  help.insert("/p2".to_string(), info_map(
+    vec![
+      ("description", "Secondary progressions (1 day = 1 year) of a natal chart to a target date with progressed bodies, MC and house cusps"),
+      ("dt", "natal date"),
+      ("dt2", "target date, default now"),
+      ("loc", "natal lat,lng(,alt) coordinates"),
+      ("bodies", "comma-separated list of required bodies, all or core, default su,mo,ma,me,ju,ve,sa"),
+      ("topo", "0: geocentric, 1: topocentric"),
+      ("eq", "0: ecliptic only, 1 equatorial only, 2: show equatorial and ecliptic, 3: show azimuth and altitide. 4: Also show other planetary phenomena"),
+      ("hsys", "Comma-separated list of house system letters or `all` for all systems, default W (whole house system)"),
+      ("pm", "progression method for the MC: sa = solar arc (default), naibod = mean solar motion of 0.98564733º per year"),
+      ("yt", "year type: tropical (default), sidereal or anomalistic"),
+      ("sid", "0: tropical (default), 1: sidereal"),
+      ("aya", "ayanamsha key for sidereal longitudes, default true_citra"),
+      ("p2", "1: show progressed longitudes at regular intervals (default, see p2yrs, p2ago, p2start, p2py and p2bodies), 0: hide them"),
+      ("iso", "0 = show julian days (default), 1 = also show ISO datetime UTC"),
+    ]
+  ));
+
+  help.insert("/directions".to_string(), info_map(
+    vec![
+      ("description", "Solar arc and primary directions of a natal chart with directed positions at the target date and the directions perfecting over the following time span"),
+      ("dt", "natal date"),
+      ("dt2", "target date, default now"),
+      ("loc", "natal lat,lng(,alt) coordinates"),
+      ("bodies", "comma-separated list of body keys, default su,mo,me,ve,ma,ju,sa. The ascendant and MC are always included"),
+      ("dm", "direction methods: sa = solar arc (default), placidus, regiomontanus, primary (both primary methods) or all"),
+      ("pm", "arc for solar arc directions: sa = solar arc (default), naibod = mean solar motion of 0.98564733º per year"),
+      ("aspects", "major (default), all or comma-separated aspect keys"),
+      ("days", "number of days to search from the target date, default 366"),
+      ("years", "number of years to search, overrides days, max 100"),
+      ("yt", "year type: tropical (default), sidereal or anomalistic"),
+      ("iso", "0 = show julian days (default), 1 = also show ISO datetime UTC"),
+    ]
+  ));
+
+  help.insert("/returns".to_string(), info_map(
+    vec![
+      ("description", "Exact times when the Sun, Moon or any body returns to its natal longitude with a full chart for each return, as in /chart-data"),
+      ("type", "solar (default), lunar or a body key, e.g. ju"),
+      ("dt2", "natal date"),
+      ("loc2", "natal lat,lng(,alt) coordinates"),
+      ("loc", "location of the return charts, default the natal location"),
+      ("year", "calendar year to search"),
+      ("month", "calendar month 1-12 within the year, e.g. for lunar returns"),
+      ("dt", "start date if no year is specified, default now"),
+      ("days", "number of days to search from the start date, default 366 or 28 for lunar returns"),
+      ("years", "number of years to search, overrides days, max 100"),
+      ("sid", "0: tropical (default), 1: sidereal returns"),
+      ("aya", "ayanamsha key for sidereal returns, default true_citra"),
+      ("iso", "0 = show julian days (default), 1 = also show ISO datetime UTC"),
+    ]
+  ));
+
+  help.insert("/composite".to_string(), info_map(
+    vec![
+      ("description", "Composite chart with the nearer midpoints of each pair of body longitudes and house cusps derived from the composite MC"),
+      ("dt", "date of the first chart"),
+      ("loc", "lat,lng(,alt) coordinates of the first chart"),
+      ("dt2", "date of the second chart"),
+      ("loc2", "lat,lng(,alt) coordinates of the second chart"),
+      ("bodies", "comma-separated list of required bodies, all or core"),
+      ("hsys", "Comma-separated list of house system letters or `all` for all systems, default W (whole house system)"),
+      ("sid", "0: tropical (default), 1: sidereal"),
+      ("aya", "ayanamsha key for sidereal longitudes, default true_citra"),
+    ]
+  ));
+
+  help.insert("/davison".to_string(), info_map(
+    vec![
+      ("description", "Davison relationship chart cast for the midpoint in time and the median latitude and longitude of both charts, with all /chart-data options"),
+      ("dt", "date of the first chart"),
+      ("loc", "lat,lng(,alt) coordinates of the first chart"),
+      ("dt2", "date of the second chart"),
+      ("loc2", "lat,lng(,alt) coordinates of the second chart"),
+    ]
+  ));
+
+  help.insert("/midpoints".to_string(), info_map(
+    vec![
+      ("description", "All pairwise midpoints of the bodies, ascendant and MC sorted on a dial, with the bodies falling on them within the orb and midpoint trees for each body"),
+      ("dt", "Date"),
+      ("loc", "lat,lng(,alt) coordinates"),
+      ("bodies", "comma-separated list of required bodies, all or core"),
+      ("topo", "0: geocentric, 1: topocentric"),
+      ("dial", "dial in degrees: 360, 90 (default), 45 or 22.5"),
+      ("orb", "orb in degrees on the dial, default 1.5"),
+      ("sid", "0: tropical (default), 1: sidereal"),
+      ("aya", "ayanamsha key for sidereal longitudes, default true_citra"),
+    ]
+  ));
+
+  help.insert("/harmonics".to_string(), info_map(
+    vec![
+      ("description", "Harmonic chart longitudes of the bodies, ascendant and MC, i.e. the longitudes multiplied by the harmonic number"),
+      ("dt", "Date"),
+      ("loc", "lat,lng(,alt) coordinates"),
+      ("bodies", "comma-separated list of required bodies, all or core"),
+      ("topo", "0: geocentric, 1: topocentric"),
+      ("harmonics", "comma-separated harmonic numbers or ranges, e.g. 5,7,9 (default) or 1-12"),
+      ("sid", "0: tropical (default), 1: sidereal"),
+      ("aya", "ayanamsha key for sidereal longitudes, default true_citra"),
+    ]
+  ));
+
+  help.insert("/stars".to_string(), info_map(
+    vec![
+      ("description", "Fixed star positions from sefstars.txt in the ephemeris path with rise, set and culmination times and parans with the bodies at the location"),
+      ("dt", "Date"),
+      ("loc", "lat,lng(,alt) coordinates"),
+      ("stars", "comma-separated star names, e.g. Regulus,Spica, default: Aldebaran, Regulus, Antares, Fomalhaut and 12 other prominent stars"),
+      ("bodies", "comma-separated list of bodies for parans, default su,mo,ma,me,ju,ve,sa"),
+      ("ct", "0: no transitions, 1: show rise, set and culmination times (default)"),
+      ("parans", "0: no parans, 1: show parans (default)"),
+      ("orb", "paran orb in degrees of diurnal rotation, default 1 (4 minutes)"),
+      ("sid", "0: tropical (default), 1: sidereal"),
+      ("aya", "ayanamsha key for sidereal longitudes, default true_citra"),
+      ("iso", "0: show julian days (default), 1: show ISO datetime UTC"),
+    ]
+  ));
+
+  help.insert("/sky".to_string(), info_map(
+    vec![
+      ("description", "Bodies above the horizon at a location sorted by apparent altitude, with azimuth, hour angle, parallactic angle and airmass"),
+      ("dt", "Date, default: current date-time"),
+      ("loc", "lat,lng(,alt) coordinates"),
+      ("bodies", "comma-separated list of bodies, default su,mo,me,ve,ma,ju,sa,ur,ne,pl"),
+      ("pres, temp", "atmospheric pressure in hPa (mbar) and temperature in ºC for refraction"),
+      ("hor", "horizon altitude in degrees, default 0"),
+    ]
+  ));
+
   help.insert("/pheno".to_string(), info_map(
     vec![
       ("dt", "current date-time"),
@@ -0,0 +1,174 @@
+use serde::{Serialize, Deserialize};
+use super::{core::{calc_body_jd_geo}, aspects::{AspectType}, planet_stations::{match_or_calc_planet_stations_range}};
+use super::models::{graha_pos::GrahaPos, general::{KeyNumValue, FlexiValue}};
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TransitAspectEvent {
+  pub key: String,
+  #[serde(rename="natalKey")]
+  pub natal_key: String,
+  pub aspect: String,
+  pub angle: f64,
+  #[serde(rename="natalLng")]
+  pub natal_lng: f64,
+  pub lng: f64,
+  #[serde(rename="lngSpeed")]
+  pub lng_speed: f64,
+  pub retro: bool,
+  pub time: FlexiValue,
+}
+
+/*
+* Normalise any angular difference to the range -180º to +180º
+*/
+pub fn normalize_180(value: f64) -> f64 {
+  let diff = value % 360f64;
+  if diff > 180f64 { diff - 360f64 } else if diff <= -180f64 { diff + 360f64 } else { diff }
+}
+
+/*
+* Sample interval in days. This must be short enough to never skip over two crossings of the same point
+* and is supplemented by planet stations where available
+*/
+pub fn transit_sample_step(key: &str) -> f64 {
+  match key {
+    "mo" => 0.25f64,
+    "me" | "ve" => 1f64,
+    "su" | "ma" | "ra" | "ke" => 2f64,
+    _ => 5f64,
+  }
+}
+
+/*
+* Sample times with longitudes for one transiting body over a time span.
+* Retrograde and direct stations from the planet station tables or the station solver are inserted,
+* so longitudes are monotonic between any two samples near stations.
+*/
+pub fn sample_transit_longitudes(key: &str, start_jd: f64, end_jd: f64) -> Vec<(f64, f64)> {
+  sample_longitudes_with(key, start_jd, end_jd, &|jd| calc_body_jd_geo(jd, key, 0f64).lng)
+}
+
+/*
+* As above with any longitude function, e.g. for sidereal longitudes
+*/
+pub fn sample_longitudes_with(key: &str, start_jd: f64, end_jd: f64, calc_lng: &dyn Fn(f64) -> f64) -> Vec<(f64, f64)> {
+  let step = transit_sample_step(key);
+  let mut jds: Vec<f64> = Vec::new();
+  let mut ref_jd = start_jd;
+  while ref_jd < end_jd {
+    jds.push(ref_jd);
+    ref_jd += step;
+  }
+  jds.push(end_jd);
+  for station in match_or_calc_planet_stations_range(key, start_jd, end_jd) {
+    if station.jd > start_jd && station.jd < end_jd {
+      jds.push(station.jd);
+    }
+  }
+  jds.sort_by(|a, b| a.partial_cmp(b).unwrap());
+  jds.dedup();
+  jds.into_iter().map(|jd| (jd, calc_lng(jd))).collect()
+}
+
+/*
+* Bisect between two sample times where the offset from the target longitude changes sign
+*/
+pub fn refine_crossing_with(calc_lng: &dyn Fn(f64) -> f64, target: f64, start_jd: f64, start_offset: f64, end_jd: f64) -> f64 {
+  let mut jd1 = start_jd;
+  let mut jd2 = end_jd;
+  let mut offset1 = start_offset;
+  for _ in 0..40 {
+    let mid_jd = (jd1 + jd2) / 2f64;
+    let mid_offset = normalize_180(calc_lng(mid_jd) - target);
+    if mid_offset == 0f64 {
+      return mid_jd;
+    }
+    if mid_offset.signum() == offset1.signum() {
+      jd1 = mid_jd;
+      offset1 = mid_offset;
+    } else {
+      jd2 = mid_jd;
+    }
+  }
+  (jd1 + jd2) / 2f64
+}
+
+/*
+* Find all times when a transiting body crosses the target longitude within the sampled time span
+*/
+pub fn match_longitude_crossings(key: &str, target: f64, samples: &Vec<(f64, f64)>) -> Vec<f64> {
+  match_crossings_with(&|jd| calc_body_jd_geo(jd, key, 0f64).lng, target, samples)
+}
+
+pub fn match_crossings_with(calc_lng: &dyn Fn(f64) -> f64, target: f64, samples: &Vec<(f64, f64)>) -> Vec<f64> {
+  let mut jds: Vec<f64> = Vec::new();
+  let num_samples = samples.len();
+  for i in 1..num_samples {
+    let (jd1, lng1) = samples[i - 1];
+    let (jd2, lng2) = samples[i];
+    let offset1 = normalize_180(lng1 - target);
+    let offset2 = normalize_180(lng2 - target);
+    // ignore the jump from +180º to -180º on the opposite side of the target
+    let is_crossing = offset1.signum() != offset2.signum() && (offset1 - offset2).abs() < 90f64;
+    if offset1 == 0f64 {
+      jds.push(jd1);
+    } else if is_crossing && offset2 != 0f64 {
+      jds.push(refine_crossing_with(calc_lng, target, jd1, offset1, jd2));
+    } else if offset2 == 0f64 && i == num_samples - 1 {
+      jds.push(jd2);
+    }
+  }
+  jds
+}
+
+/*
+* Exact aspect times of transiting bodies to natal positions over a date range.
+* Both waxing and waning aspects are matched, e.g. transiting Saturn at +90º and -90º from the natal Sun,
+* including repeated passes during retrograde periods.
+*/
+pub fn calc_transit_aspects(keys: Vec<&str>, natal_positions: &Vec<GrahaPos>, aspect_types: &Vec<AspectType>, start_jd: f64, end_jd: f64, iso_mode: bool) -> Vec<TransitAspectEvent> {
+  let mut items: Vec<(f64, TransitAspectEvent)> = Vec::new();
+  for key in keys {
+    let samples = sample_transit_longitudes(key, start_jd, end_jd);
+    for natal in natal_positions {
+      for aspect in aspect_types {
+        let angle = aspect.angle();
+        let mut targets = vec![(natal.lng + angle) % 360f64];
+        if angle > 0f64 && angle < 180f64 {
+          targets.push((natal.lng + 360f64 - angle) % 360f64);
+        }
+        for target in targets {
+          for jd in match_longitude_crossings(key, target, &samples) {
+            let pos = calc_body_jd_geo(jd, key, 0f64);
+            items.push((jd, TransitAspectEvent {
+              key: key.to_string(),
+              natal_key: natal.key.clone(),
+              aspect: aspect.as_key().to_string(),
+              angle,
+              natal_lng: natal.lng,
+              lng: pos.lng,
+              lng_speed: pos.lng_speed,
+              retro: pos.lng_speed < 0f64,
+              time: KeyNumValue::new("exact", jd).as_flexi_value(iso_mode),
+            }));
+          }
+        }
+      }
+    }
+  }
+  items.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+  items.into_iter().map(|(_, item)| item).collect()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::normalize_180;
+
+  #[test]
+  fn normalizes_angles_across_zero_aries() {
+    assert_eq!(normalize_180(350f64 - 10f64), -20f64);
+    assert_eq!(normalize_180(10f64 - 350f64), 20f64);
+    assert_eq!(normalize_180(180f64), 180f64);
+    assert_eq!(normalize_180(-180f64), 180f64);
+  }
+}
@@ -0,0 +1,303 @@
+use serde::{Serialize, Deserialize};
+use std::collections::HashMap;
+use super::models::{graha_pos::GrahaPos, geo_pos::GeoPos, houses::{AscMc, get_house_data}};
+use super::core::{get_bodies_ecl_geo, get_bodies_ecl_topo};
+use super::utils::converters::{to_str_refs};
+
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq)]
+pub enum AspectType {
+  Conjunction,
+  Opposition,
+  Trine,
+  Square,
+  Sextile,
+  Quincunx,
+  SemiSextile,
+  SemiSquare,
+  Sesquiquadrate,
+  Quintile,
+  BiQuintile,
+}
+
+impl AspectType {
+
+  pub fn angle(&self) -> f64 {
+    match self {
+      AspectType::Conjunction => 0f64,
+      AspectType::Opposition => 180f64,
+      AspectType::Trine => 120f64,
+      AspectType::Square => 90f64,
+      AspectType::Sextile => 60f64,
+      AspectType::Quincunx => 150f64,
+      AspectType::SemiSextile => 30f64,
+      AspectType::SemiSquare => 45f64,
+      AspectType::Sesquiquadrate => 135f64,
+      AspectType::Quintile => 72f64,
+      AspectType::BiQuintile => 144f64,
+    }
+  }
+
+  pub fn as_key(&self) -> &str {
+    match self {
+      AspectType::Conjunction => "conjunction",
+      AspectType::Opposition => "opposition",
+      AspectType::Trine => "trine",
+      AspectType::Square => "square",
+      AspectType::Sextile => "sextile",
+      AspectType::Quincunx => "quincunx",
+      AspectType::SemiSextile => "semi-sextile",
+      AspectType::SemiSquare => "semi-square",
+      AspectType::Sesquiquadrate => "sesquiquadrate",
+      AspectType::Quintile => "quintile",
+      AspectType::BiQuintile => "bi-quintile",
+    }
+  }
+
+  pub fn is_major(&self) -> bool {
+    match self {
+      AspectType::Conjunction | AspectType::Opposition | AspectType::Trine | AspectType::Square | AspectType::Sextile => true,
+      _ => false,
+    }
+  }
+
+  /*
+  * Proportion of the body orb allowed for this aspect type
+  */
+  pub fn orb_factor(&self) -> f64 {
+    match self {
+      AspectType::Sextile => 0.75f64,
+      _ => if self.is_major() { 1f64 } else { 0.25f64 },
+    }
+  }
+
+  pub fn from_key(key: &str) -> Option<AspectType> {
+    let simple_key = key.to_lowercase().replace("-", "").replace("_", "");
+    match simple_key.as_str() {
+      "cj" | "conjunction" => Some(AspectType::Conjunction),
+      "op" | "opposition" => Some(AspectType::Opposition),
+      "tr" | "trine" => Some(AspectType::Trine),
+      "sq" | "square" => Some(AspectType::Square),
+      "sx" | "sextile" => Some(AspectType::Sextile),
+      "qx" | "quincunx" | "inconjunct" => Some(AspectType::Quincunx),
+      "ss" | "semisextile" => Some(AspectType::SemiSextile),
+      "sm" | "semisquare" => Some(AspectType::SemiSquare),
+      "sqq" | "sesquiquadrate" | "sesquisquare" => Some(AspectType::Sesquiquadrate),
+      "qn" | "quintile" => Some(AspectType::Quintile),
+      "bq" | "biquintile" => Some(AspectType::BiQuintile),
+      _ => None,
+    }
+  }
+}
+
+pub fn major_aspect_types() -> Vec<AspectType> {
+  vec![AspectType::Conjunction, AspectType::Opposition, AspectType::Trine, AspectType::Square, AspectType::Sextile]
+}
+
+pub fn all_aspect_types() -> Vec<AspectType> {
+  vec![
+    AspectType::Conjunction,
+    AspectType::Opposition,
+    AspectType::Trine,
+    AspectType::Square,
+    AspectType::Sextile,
+    AspectType::Quincunx,
+    AspectType::SemiSextile,
+    AspectType::SemiSquare,
+    AspectType::Sesquiquadrate,
+    AspectType::Quintile,
+    AspectType::BiQuintile,
+  ]
+}
+
+/*
+* Match aspect types from a query string value. 1 or major yields the major aspects, all adds the minor aspects,
+* otherwise a comma-separated list of aspect keys is expected
+*/
+pub fn match_aspect_types(ref_str: &str) -> Vec<AspectType> {
+  match ref_str.to_lowercase().as_str() {
+    "" | "0" => vec![],
+    "1" | "major" => major_aspect_types(),
+    "2" | "all" => all_aspect_types(),
+    _ => ref_str.split(",").filter_map(|k| AspectType::from_key(k.trim())).collect(),
+  }
+}
+
+pub fn default_body_orb(key: &str) -> f64 {
+  match key {
+    "su" | "mo" => 10f64,
+    "me" | "ve" | "ma" => 7f64,
+    "ju" | "sa" => 6f64,
+    _ => 5f64,
+  }
+}
+
+/*
+* Orbs per body key. Any body not overridden falls back to the default orb for its class
+*/
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct AspectOrbs {
+  pub orbs: HashMap<String, f64>,
+}
+
+impl AspectOrbs {
+  pub fn new(orbs: HashMap<String, f64>) -> AspectOrbs {
+    AspectOrbs { orbs }
+  }
+
+  pub fn default() -> AspectOrbs {
+    AspectOrbs { orbs: HashMap::new() }
+  }
+
+  pub fn body_orb(&self, key: &str) -> f64 {
+    match self.orbs.get(key) {
+      Some(orb) => *orb,
+      None => default_body_orb(key),
+    }
+  }
+
+  pub fn pair_orb(&self, key1: &str, key2: &str, aspect: AspectType) -> f64 {
+    let orb1 = self.body_orb(key1);
+    let orb2 = self.body_orb(key2);
+    let orb = if orb1 > orb2 { orb1 } else { orb2 };
+    orb * aspect.orb_factor()
+  }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct AspectResult {
+  pub key1: String,
+  pub key2: String,
+  pub aspect: String,
+  pub angle: f64,
+  pub distance: f64,
+  pub orb: f64,
+  #[serde(rename="maxOrb")]
+  pub max_orb: f64,
+  pub exactness: f64,
+  pub applying: bool,
+}
+
+/*
+* Shortest angular distance between two longitudes from 0º to 180º
+*/
+pub fn calc_angular_distance(lng1: f64, lng2: f64) -> f64 {
+  let diff = (lng2 - lng1).abs() % 360f64;
+  if diff > 180f64 { 360f64 - diff } else { diff }
+}
+
+/*
+* Check if the aspect is tightening by projecting both bodies forward over a short interval
+* via their longitudinal speeds. Angles and static points have zero speed
+*/
+pub fn is_applying(lng1: f64, speed1: f64, lng2: f64, speed2: f64, target: f64) -> bool {
+  let interval = 0.01f64;
+  let orb_now = (calc_angular_distance(lng1, lng2) - target).abs();
+  let orb_next = (calc_angular_distance(lng1 + speed1 * interval, lng2 + speed2 * interval) - target).abs();
+  orb_next < orb_now
+}
+
+pub fn match_aspect(pos1: &GrahaPos, pos2: &GrahaPos, aspect_types: &Vec<AspectType>, orbs: &AspectOrbs) -> Option<AspectResult> {
+  let distance = calc_angular_distance(pos1.lng, pos2.lng);
+  let mut matched: Option<AspectResult> = None;
+  for aspect in aspect_types {
+    let target = aspect.angle();
+    let orb = (distance - target).abs();
+    let max_orb = orbs.pair_orb(pos1.key.as_str(), pos2.key.as_str(), *aspect);
+    let is_closer = match &matched {
+      Some(prev) => orb < prev.orb,
+      None => true,
+    };
+    if orb <= max_orb && is_closer {
+      let exactness = if max_orb > 0f64 { 1f64 - orb / max_orb } else { 1f64 };
+      matched = Some(AspectResult {
+        key1: pos1.key.clone(),
+        key2: pos2.key.clone(),
+        aspect: aspect.as_key().to_string(),
+        angle: target,
+        distance,
+        orb,
+        max_orb,
+        exactness,
+        applying: is_applying(pos1.lng, pos1.lng_speed, pos2.lng, pos2.lng_speed, target),
+      });
+    }
+  }
+  matched
+}
+
+/*
+* Elevate the ascendant and MC to notional bodies without speed so they can be aspected
+*/
+pub fn asc_mc_to_graha_positions(points: &AscMc) -> Vec<GrahaPos> {
+  vec![GrahaPos::basic("as", points.ascendant), GrahaPos::basic("mc", points.mc)]
+}
+
+/*
+* Tropical ecliptic body positions with the ascendant and MC for a chart that may be aspected
+*/
+pub fn calc_chart_aspect_positions(jd: f64, geo: GeoPos, keys: &Vec<String>, topo: bool) -> Vec<GrahaPos> {
+  let mut positions = match topo {
+    true => get_bodies_ecl_topo(jd, to_str_refs(keys), geo, 0f64),
+    _ => get_bodies_ecl_geo(jd, to_str_refs(keys), 0f64),
+  };
+  let house_data = get_house_data(jd, geo.lat, geo.lng, 'W', false);
+  positions.append(&mut asc_mc_to_graha_positions(&house_data.points));
+  positions
+}
+
+/*
+* Aspects between all bodies within one chart
+*/
+pub fn calc_aspects(positions: &Vec<GrahaPos>, aspect_types: &Vec<AspectType>, orbs: &AspectOrbs) -> Vec<AspectResult> {
+  let mut items: Vec<AspectResult> = Vec::new();
+  let num_items = positions.len();
+  for i in 0..num_items {
+    for j in (i + 1)..num_items {
+      if let Some(result) = match_aspect(&positions[i], &positions[j], aspect_types, orbs) {
+        items.push(result);
+      }
+    }
+  }
+  items
+}
+
+/*
+* Aspects between bodies in the first chart and bodies in the second chart, e.g. for synastry
+*/
+pub fn calc_cross_aspects(positions1: &Vec<GrahaPos>, positions2: &Vec<GrahaPos>, aspect_types: &Vec<AspectType>, orbs: &AspectOrbs) -> Vec<AspectResult> {
+  let mut items: Vec<AspectResult> = Vec::new();
+  for pos1 in positions1 {
+    for pos2 in positions2 {
+      if let Some(result) = match_aspect(pos1, pos2, aspect_types, orbs) {
+        items.push(result);
+      }
+    }
+  }
+  items
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn matches_aspects_across_zero_aries() {
+    let orbs = AspectOrbs::default();
+    let pos1 = GrahaPos::new("ma", 355f64, 0f64, -0.3f64, 0f64);
+    let pos2 = GrahaPos::new("sa", 88f64, 0f64, 0.02f64, 0f64);
+    let result = match_aspect(&pos1, &pos2, &major_aspect_types(), &orbs).unwrap();
+    assert_eq!(result.aspect, "square");
+    assert_eq!(result.distance, 93f64);
+    assert!(!result.applying);
+  }
+
+  #[test]
+  fn detects_applying_aspects() {
+    let orbs = AspectOrbs::default();
+    let pos1 = GrahaPos::new("mo", 115f64, 0f64, 13f64, 0f64);
+    let pos2 = GrahaPos::new("su", 0f64, 0f64, 1f64, 0f64);
+    let result = match_aspect(&pos1, &pos2, &major_aspect_types(), &orbs).unwrap();
+    assert_eq!(result.aspect, "trine");
+    assert!(result.applying);
+  }
+}
@@ -0,0 +1,31 @@
+use serde::{Serialize, Deserialize};
+use super::{core::{get_bodies_ecl_geo}, math_funcs::{calc_mid_lng, subtract_360, mid_point_to_surface}};
+use super::models::{geo_pos::GeoPos, general::{LngLatKey}, houses::{HouseSetData, get_house_data, get_house_systems_for_mc}};
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CompositeChart {
+  pub jd: f64,
+  pub geo: GeoPos,
+  pub mc: f64,
+  pub bodies: Vec<LngLatKey>,
+  pub house: HouseSetData,
+}
+
+/*
+* Composite chart from the nearer midpoints of each pair of tropical body longitudes. The house cusps are derived
+* from the composite MC at the geographic midpoint of both locations
+*/
+pub fn calc_composite_chart(jd1: f64, geo1: GeoPos, jd2: f64, geo2: GeoPos, keys: Vec<&str>, h_systems: Vec<char>, aya_offset: f64) -> CompositeChart {
+  let bodies1 = get_bodies_ecl_geo(jd1, keys.clone(), 0f64);
+  let bodies2 = get_bodies_ecl_geo(jd2, keys, 0f64);
+  let bodies: Vec<LngLatKey> = bodies1.iter().zip(bodies2.iter()).map(|(b1, b2)| {
+    LngLatKey::new(b1.key.as_str(), subtract_360(calc_mid_lng(b1.lng, b2.lng), aya_offset), (b1.lat + b2.lat) / 2f64)
+  }).collect();
+  let mc1 = get_house_data(jd1, geo1.lat, geo1.lng, 'W', false).points.mc;
+  let mc2 = get_house_data(jd2, geo2.lat, geo2.lng, 'W', false).points.mc;
+  let mc = calc_mid_lng(mc1, mc2);
+  let jd = (jd1 + jd2) / 2f64;
+  let geo = mid_point_to_surface(geo1, geo2);
+  let house = get_house_systems_for_mc(jd, geo, mc, h_systems, aya_offset);
+  CompositeChart { jd, geo, mc: subtract_360(mc, aya_offset), bodies, house }
+}
@@ -1,11 +1,42 @@
 use math::round::{floor};
 use libswe_sys::sweconst::{Bodies, OptionalFlag};
 use libswe_sys::swerust::{handler_swe03::*};
-use super::{settings::{ayanamshas::*},traits::*, math_funcs::{calc_progress_day_jds_by_year, adjust_lng_by_body_key, calc_opposite}, math_funcs::{subtract_360}, transitions::{get_pheno_result}, transposed_transitions::{calc_transitions_from_source_refs_minmax}};
+use super::{settings::{ayanamshas::*, graha_values::{match_body_num, is_numbered_asteroid}},traits::*, math_funcs::{calc_progress_day_jds_by_year, adjust_lng_by_body_key, calc_opposite, get_year_length}, math_funcs::{subtract_360}, transitions::{get_pheno_result}, progressions::{calc_progressed_ascendant, match_year_type, ProgressionMethod}, transposed_transitions::{calc_transitions_from_source_refs_minmax}};
 use super::models::{graha_pos::*, geo_pos::*, general::*, houses::{calc_ascendant}};
-use super::super::extensions::swe::{azalt, set_topo, set_sid_mode, get_ayanamsha};
+use super::super::extensions::{swe::{azalt, azalt_atmosphere, get_ayanamsha, calc_ut_num, Atmosphere}, ephemeris::{with_topo, with_sid_mode}};
 use std::collections::{HashMap};
 
+/*
+* Numbered asteroids such as Eris, Sedna or ast:433 are calculated by their Swiss Ephemeris number
+*/
+pub fn calc_ut_key(jd: f64, key: &str, iflag: i32) -> CalcUtResult {
+  if is_numbered_asteroid(key) {
+    calc_ut_num(jd, match_body_num(key).unwrap_or(0), iflag)
+  } else {
+    calc_ut(jd, Bodies::from_key(key), iflag)
+  }
+}
+
+/*
+* Swiss Ephemeris error for the first numbered asteroid that cannot be calculated,
+* e.g. when its asteroid file is missing from the ephemeris path
+*/
+pub fn match_numbered_asteroid_error(jd: f64, keys: &[String]) -> Option<String> {
+  let asteroid_keys: Vec<String> = keys.iter().filter(|key| is_numbered_asteroid(key)).map(|key| key.to_owned()).collect();
+  match_calc_error(jd, &asteroid_keys)
+}
+
+/*
+* Swiss Ephemeris error for the first body that cannot be calculated, e.g. Chiron or a numbered asteroid
+* without its ephemeris file
+*/
+pub fn match_calc_error(jd: f64, keys: &[String]) -> Option<String> {
+  keys.iter().find_map(|key| {
+    let result = calc_ut_key(jd, key.as_str(), OptionalFlag::Speed as i32);
+    if result.status < 0 { Some(format!("{}: {}", key, result.serr)) } else { None }
+  })
+}
+
 pub fn calc_body_jd(jd: f64, key: &str, sidereal: bool, topo: bool, aya_offset: f64) -> GrahaPos {
   let combo: i32;
   let speed_flag = OptionalFlag::Speed as i32;
@@ -23,7 +54,7 @@ pub fn calc_body_jd(jd: f64, key: &str, sidereal: bool, topo: bool, aya_offset:
       combo = speed_flag;
     }
   }
-  let result = calc_ut(jd, Bodies::from_key(key), combo);
+  let result = calc_ut_key(jd, key, combo);
   // only apply for ecliptic lng if the sidereal mode is not applied via SE in conjunction with set_sid_mode
   let aya_offset_val = if sidereal { 0f64 } else { aya_offset };
   let lng = subtract_360(adjust_lng_by_body_key(key, result.longitude), aya_offset_val);
@@ -44,7 +75,7 @@ pub fn calc_body_eq_jd_swe(jd: f64, key: &str, topo: bool) -> GrahaPos {
   } else {
     combo = speed_flag | eq_flag;
   }
-  let result = calc_ut(jd, Bodies::from_key(key), combo);
+  let result = calc_ut_key(jd, key, combo);
   let lng = adjust_lng_by_body_key(key, result.longitude);
   GrahaPos::new_eq(key, result.longitude, result.latitude, lng, result.speed_latitude)
 }
@@ -75,8 +106,8 @@ pub fn calc_body_dual_jd(jd: f64, key: &str, topo: bool, show_pheno: bool, geo_o
     combo = speed_flag | eq_flag;
   }
   let combo_geo = if topo { speed_flag | OptionalFlag::TopocentricPosition as i32 } else { speed_flag };
-  let result = calc_ut(jd, Bodies::from_key(key), combo);
-  let result_ec = calc_ut(jd, Bodies::from_key(key), combo_geo);
+  let result = calc_ut_key(jd, key, combo);
+  let result_ec = calc_ut_key(jd, key, combo_geo);
   let pheno = if show_pheno { Some(get_pheno_result(jd, key, 0i32)) } else { None };
   let lng = subtract_360(adjust_lng_by_body_key(key, result_ec.longitude), aya_offset);
   // let ra = adjust_lng_by_body_key(key, result.longitude);
@@ -101,13 +132,11 @@ pub fn calc_body_dual_jd_geo(jd: f64, key: &str, show_pheno: bool, aya_offset: f
 }
 
 pub fn calc_body_dual_jd_topo(jd: f64, key: &str, geo: GeoPos, show_pheno: bool, aya_offset: f64) -> GrahaPos {
-  set_topo(geo.lat, geo.lng, geo.alt);
-  calc_body_dual_jd(jd, key, true, show_pheno, Some(geo), aya_offset)
+  with_topo(geo.lat, geo.lng, geo.alt, || calc_body_dual_jd(jd, key, true, show_pheno, Some(geo), aya_offset))
 }
 
 pub fn calc_body_eq_jd_topo(jd: f64, key: &str, geo: GeoPos) -> GrahaPos {
-  set_topo(geo.lat, geo.lng, geo.alt);
-  calc_body_eq_jd(jd, key, true)
+  with_topo(geo.lat, geo.lng, geo.alt, || calc_body_eq_jd(jd, key, true))
 }
 
 /*
@@ -174,25 +203,21 @@ pub fn calc_bodies_positions_jd(jd_start: f64, keys: Vec<&str>, days: u16, num_p
  Get sidereal geocentric coordinates with an ayanamsha key
 */
 pub fn calc_body_jd_geo_sidereal(jd: f64, key: &str, aya_key: &str) -> GrahaPos {
-  set_sid_mode(Ayanamsha::from_key(aya_key).as_i32());
-  calc_body_jd(jd, key, true, false, 0f64)
+  with_sid_mode(Ayanamsha::from_key(aya_key).as_i32(), || calc_body_jd(jd, key, true, false, 0f64))
 }
 
 /*
  Get tropical topocentric coordinates with geo-coordinates
 */
 pub fn calc_body_jd_topo(jd: f64, key: &str, geo: GeoPos, aya_offset: f64) -> GrahaPos {
-  set_topo(geo.lat, geo.lng, geo.alt);
-  calc_body_jd(jd, key, false, true, aya_offset)
+  with_topo(geo.lat, geo.lng, geo.alt, || calc_body_jd(jd, key, false, true, aya_offset))
 }
 
 /*
  Get sidereal topocentric coordinates with geo-coordinates and an ayanamsha key
 */
 pub fn calc_body_jd_topo_sidereal(jd: f64, key: &str, geo: GeoPos, aya_key: &str) -> GrahaPos {
-  set_topo(geo.lat, geo.lng, geo.alt);
-  set_sid_mode(Ayanamsha::from_key(aya_key).as_i32());
-  calc_body_jd(jd, key, false, true, 0f64)
+  with_topo(geo.lat, geo.lng, geo.alt, || with_sid_mode(Ayanamsha::from_key(aya_key).as_i32(), || calc_body_jd(jd, key, false, true, 0f64)))
 }
 
 /*
@@ -243,16 +268,23 @@ pub fn get_bodies_ecl_topo(jd: f64, keys: Vec<&str>, geo: GeoPos, aya_offset: f6
   bodies
 }
 
-pub fn get_bodies_p2(jd: f64, keys: Vec<String>, start_year: u32, num_years: u16, per_year: u8) -> Vec<ProgressItemSet> {
+/*
+* Progressed longitudes at regular intervals from the start year, with the ascendant progressed by solar arc or Naibod arc
+*/
+pub fn get_bodies_p2(jd: f64, geo: GeoPos, keys: Vec<String>, start_year: u32, num_years: u16, per_year: u8, year_type: &str, method: ProgressionMethod) -> Vec<ProgressItemSet> {
   let mut items: Vec<ProgressItemSet> = Vec::new();
-  let jd_pairs = calc_progress_day_jds_by_year(jd, start_year, num_years, per_year);
+  let jd_pairs = calc_progress_day_jds_by_year(jd, start_year, num_years, per_year, year_type);
+  let year_length = get_year_length(match_year_type(year_type));
   for pair in jd_pairs {
     let (ref_pd, ref_jd) = pair;
     let ayanamsha = get_ayanamsha_value(ref_pd, "true_citra");
     let mut body_items: Vec<KeyNumValue> = Vec::new();
     for key in keys.clone() {
-      let result = calc_body_jd_geo(jd, key.as_str(), 0f64);
-      body_items.push(KeyNumValue::new(key.as_str(), result.lng));
+      let lng = match key.as_str() {
+        "as" => calc_progressed_ascendant(jd, ref_pd, geo, method, (ref_jd - jd) / year_length),
+        _ => calc_body_jd_geo(ref_pd, key.as_str(), 0f64).lng,
+      };
+      body_items.push(KeyNumValue::new(key.as_str(), lng));
     }
     items.push(ProgressItemSet::new(ref_pd, ref_jd, body_items, ayanamsha));
   }
@@ -313,6 +345,14 @@ pub fn calc_altitude(tjd_ut: f64, is_equal: bool, geo_lat: f64, geo_lng: f64, ln
   azalt(tjd_ut, is_equal, geo_lat, geo_lng, lng, lat).value
 }
 
+/*
+* Apparent altitude with refraction for the observer's altitude and atmospheric conditions, otherwise the true altitude
+*/
+pub fn calc_altitude_atmosphere(tjd_ut: f64, is_equal: bool, geo: GeoPos, atmosphere: Atmosphere, lng: f64, lat: f64) -> f64 {
+  let result = azalt_atmosphere(tjd_ut, is_equal, geo, atmosphere, lng, lat);
+  if atmosphere.refraction { result.apparent } else { result.value }
+}
+
 /*
 * Match the projected altitude of any celestial object
 */
@@ -334,31 +374,30 @@ pub fn calc_altitude_object(tjd_ut: f64, is_equal: bool, geo_lat: f64, geo_lng:
 
 
 
-pub fn calc_next_prev_horizon(jd: f64, geo_lat: f64, geo_lng: f64, key: &str, down: bool, next: bool) -> f64 {
+pub fn calc_next_prev_horizon(jd: f64, geo: GeoPos, atmosphere: Atmosphere, key: &str, down: bool, next: bool) -> f64 {
   let unit = if next { 1f64 } else { -1f64 };
-  let mut alt = calc_altitude_object(jd, false, geo_lat, geo_lng, key);
+  let mut alt = calc_altitude_object(jd, false, geo.lat, geo.lng, key);
   let mut days: u16 = 1;
   let mut day_jd = 0f64;
-  while ((down && alt < 0f64) || (!down && alt > 0f64)) && days < 184 {
+  while ((down && alt < atmosphere.horizon) || (!down && alt > atmosphere.horizon)) && days < 184 {
     let ref_jd = jd + (unit * days as f64);
-    alt = calc_altitude_object(ref_jd, false, geo_lat, geo_lng, key);
+    alt = calc_altitude_object(ref_jd, false, geo.lat, geo.lng, key);
     days += 1;
     day_jd = ref_jd.clone();
   }
   if day_jd > 100f64 { 
-    let geo = GeoPos::simple(geo_lat, geo_lng);
-    let mut base = calc_transitions_from_source_refs_minmax(day_jd, key, geo);
+    let mut base = calc_transitions_from_source_refs_minmax(day_jd, key, geo, atmosphere);
     
     let mut new_day_jd = if (down && !next) || (!down && next) { base.set } else { base.rise };
     
     if new_day_jd < 100f64 {
-      let day_down = base.min < 0f64 && base.max < 0f64;
+      let day_down = base.min < atmosphere.horizon && base.max < atmosphere.horizon;
       let next_jd = if (day_down && next) || (!day_down && !next)  { day_jd + 1f64 } else { day_jd - 1f64 };
-      base = calc_transitions_from_source_refs_minmax(next_jd, key, geo);
+      base = calc_transitions_from_source_refs_minmax(next_jd, key, geo, atmosphere);
       new_day_jd = if (down && !next) || (!down && next) { base.set } else { base.rise };
       if new_day_jd < 100f64 {
         let next_jd = if (day_down && next) || (!day_down && !next)  { day_jd - 1f64 } else { day_jd + 1f64 };
-        base = calc_transitions_from_source_refs_minmax(next_jd, key, geo);
+        base = calc_transitions_from_source_refs_minmax(next_jd, key, geo, atmosphere);
         new_day_jd = if (down && !next) || (!down && next) { base.set } else { base.rise };
       }
     }
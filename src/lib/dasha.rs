@@ -0,0 +1,147 @@
+use serde::{Serialize, Deserialize};
+use super::{core::{calc_body_jd_geo, get_ayanamsha_value}, math_funcs::{calc_nakshatra_27, get_year_length, subtract_360}, julian_date::{julian_day_to_iso_datetime}, settings::ayanamshas::{match_ayanamsha_key}};
+
+/*
+* Vimshottari lords in sequence from Ashwini with their mahadasha durations in years, 120 in total
+*/
+pub const VIMSHOTTARI_LORDS: [(&str, f64); 9] = [
+  ("ke", 7f64),
+  ("ve", 20f64),
+  ("su", 6f64),
+  ("mo", 10f64),
+  ("ma", 7f64),
+  ("ra", 18f64),
+  ("ju", 16f64),
+  ("sa", 19f64),
+  ("me", 17f64),
+];
+
+pub const VIMSHOTTARI_YEARS: f64 = 120f64;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct DashaPeriod {
+  pub key: String,
+  pub start: f64,
+  pub end: f64,
+  pub years: f64,
+  #[serde(rename="startDt",skip_serializing_if = "String::is_empty")]
+  pub start_dt: String,
+  #[serde(rename="endDt",skip_serializing_if = "String::is_empty")]
+  pub end_dt: String,
+  #[serde(skip_serializing_if = "Vec::is_empty")]
+  pub periods: Vec<DashaPeriod>,
+}
+
+impl DashaPeriod {
+  pub fn new(key: &str, start: f64, end: f64, years: f64, periods: Vec<DashaPeriod>, iso_mode: bool) -> DashaPeriod {
+    let start_dt = if iso_mode { julian_day_to_iso_datetime(start) } else { "".to_string() };
+    let end_dt = if iso_mode { julian_day_to_iso_datetime(end) } else { "".to_string() };
+    DashaPeriod { key: key.to_string(), start, end, years, start_dt, end_dt, periods }
+  }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct DashaSet {
+  pub system: String,
+  pub ayanamsha: String,
+  #[serde(rename="ayanamshaValue")]
+  pub ayanamsha_value: f64,
+  #[serde(rename="yearLength")]
+  pub year_length: f64,
+  #[serde(rename="moonLng")]
+  pub moon_lng: f64,
+  pub nakshatra: u8,
+  #[serde(rename="nakshatraProgress")]
+  pub nakshatra_progress: f64,
+  #[serde(rename="balanceYears")]
+  pub balance_years: f64,
+  pub periods: Vec<DashaPeriod>,
+}
+
+fn lord_index(key: &str) -> usize {
+  VIMSHOTTARI_LORDS.iter().position(|(k, _)| *k == key).unwrap_or(0)
+}
+
+/*
+* Sub-periods within a parent period, starting with the parent lord and proportional to the mahadasha years of each lord
+*/
+pub fn calc_dasha_sub_periods(parent_key: &str, start_jd: f64, parent_years: f64, year_length: f64, levels: u8, iso_mode: bool) -> Vec<DashaPeriod> {
+  let mut items: Vec<DashaPeriod> = Vec::new();
+  let first_index = lord_index(parent_key);
+  let mut ref_jd = start_jd;
+  for i in 0..9 {
+    let (key, lord_years) = VIMSHOTTARI_LORDS[(first_index + i) % 9];
+    let years = parent_years * lord_years / VIMSHOTTARI_YEARS;
+    let end_jd = ref_jd + years * year_length;
+    let periods = if levels > 1 { calc_dasha_sub_periods(key, ref_jd, years, year_length, levels - 1, iso_mode) } else { vec![] };
+    items.push(DashaPeriod::new(key, ref_jd, end_jd, years, periods, iso_mode));
+    ref_jd = end_jd;
+  }
+  items
+}
+
+/*
+* Vimshottari mahadashas from the sidereal Moon longitude at birth with up to 3 levels (mahadasha, antardasha and pratyantardasha).
+* The first mahadasha starts before birth by the elapsed proportion of the Moon's nakshatra
+*/
+pub fn calc_vimshottari_periods(moon_lng: f64, jd: f64, year_length: f64, levels: u8, iso_mode: bool) -> (u8, f64, f64, Vec<DashaPeriod>) {
+  let (nakshatra_index, progress) = calc_nakshatra_27(moon_lng);
+  let first_index = nakshatra_index as usize % 9;
+  let (_, first_years) = VIMSHOTTARI_LORDS[first_index];
+  let balance_years = first_years * (1f64 - progress);
+  let start_jd = jd - first_years * progress * year_length;
+  let mut items: Vec<DashaPeriod> = Vec::new();
+  let mut ref_jd = start_jd;
+  for i in 0..9 {
+    let (key, years) = VIMSHOTTARI_LORDS[(first_index + i) % 9];
+    let end_jd = ref_jd + years * year_length;
+    let periods = if levels > 1 { calc_dasha_sub_periods(key, ref_jd, years, year_length, levels - 1, iso_mode) } else { vec![] };
+    items.push(DashaPeriod::new(key, ref_jd, end_jd, years, periods, iso_mode));
+    ref_jd = end_jd;
+  }
+  (nakshatra_index + 1, progress, balance_years, items)
+}
+
+pub fn calc_vimshottari_dasha(jd: f64, aya_key: &str, year_type: &str, levels: u8, iso_mode: bool) -> DashaSet {
+  let ayanamsha_value = get_ayanamsha_value(jd, aya_key);
+  let moon = calc_body_jd_geo(jd, "mo", 0f64);
+  let moon_lng = subtract_360(moon.lng, ayanamsha_value);
+  let year_length = get_year_length(year_type);
+  let num_levels = if levels < 1 { 1 } else if levels > 3 { 3 } else { levels };
+  let (nakshatra, nakshatra_progress, balance_years, periods) = calc_vimshottari_periods(moon_lng, jd, year_length, num_levels, iso_mode);
+  DashaSet {
+    system: "vimshottari".to_string(),
+    ayanamsha: match_ayanamsha_key(aya_key),
+    ayanamsha_value,
+    year_length,
+    moon_lng,
+    nakshatra,
+    nakshatra_progress,
+    balance_years,
+    periods,
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn has_correct_vimshottari_balance_and_sequence() {
+    let year_length = 365.25f64;
+    let birth_jd = 2451545f64;
+    // midway through Bharani, ruled by Venus
+    let moon_lng = 360f64 / 27f64 * 1.5f64;
+    let (nakshatra, _, balance_years, periods) = calc_vimshottari_periods(moon_lng, birth_jd, year_length, 2, false);
+    assert_eq!(nakshatra, 2);
+    assert!((balance_years - 10f64).abs() < 0.000001f64);
+    assert_eq!(periods[0].key, "ve");
+    assert_eq!(periods[1].key, "su");
+    assert!((periods[0].end - (birth_jd + 10f64 * year_length)).abs() < 0.0001f64);
+    let total_years: f64 = periods.iter().map(|p| p.years).sum();
+    assert_eq!(total_years, VIMSHOTTARI_YEARS);
+    let sub_years: f64 = periods[0].periods.iter().map(|p| p.years).sum();
+    assert!((sub_years - 20f64).abs() < 0.000001f64);
+    assert_eq!(periods[0].periods[1].key, "su");
+  }
+}
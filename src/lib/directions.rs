@@ -0,0 +1,318 @@
+use serde::{Serialize, Deserialize};
+use super::{core::{calc_body_jd_geo, calc_body_dual_jd_geo, ecliptic_to_equatorial_basic}, julian_date::{julian_day_to_iso_datetime}, math_funcs::{get_year_length}, aspect_transits::{normalize_180}};
+use super::progressions::{ProgressionMethod, NAIBOD_ARC, calc_mc_arc};
+use super::aspects::{AspectType};
+use super::models::{geo_pos::GeoPos, houses::{get_house_data}};
+
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq)]
+pub enum DirectionMethod {
+  SolarArc,
+  Placidus,
+  Regiomontanus,
+}
+
+impl DirectionMethod {
+  pub fn as_key(&self) -> &str {
+    match self {
+      DirectionMethod::SolarArc => "solar_arc",
+      DirectionMethod::Placidus => "placidus",
+      DirectionMethod::Regiomontanus => "regiomontanus",
+    }
+  }
+}
+
+/*
+* Match direction methods from a query string value: solar arc by default, primary for both primary methods,
+* all or a comma-separated list of method keys
+*/
+pub fn match_direction_methods(ref_str: &str) -> Vec<DirectionMethod> {
+  match ref_str.to_lowercase().as_str() {
+    "all" => vec![DirectionMethod::SolarArc, DirectionMethod::Placidus, DirectionMethod::Regiomontanus],
+    "primary" | "pd" => vec![DirectionMethod::Placidus, DirectionMethod::Regiomontanus],
+    _ => {
+      let mut methods: Vec<DirectionMethod> = Vec::new();
+      for key in ref_str.to_lowercase().split(",") {
+        let method = match key.trim() {
+          "placidus" | "p" | "semi_arc" => Some(DirectionMethod::Placidus),
+          "regiomontanus" | "r" | "regio" => Some(DirectionMethod::Regiomontanus),
+          "solar_arc" | "sa" => Some(DirectionMethod::SolarArc),
+          _ => None,
+        };
+        if let Some(m) = method {
+          if !methods.contains(&m) {
+            methods.push(m);
+          }
+        }
+      }
+      if methods.len() > 0 { methods } else { vec![DirectionMethod::SolarArc] }
+    }
+  }
+}
+
+/*
+* Natal point with ecliptic and equatorial coordinates. The ascendant and MC lie on the ecliptic
+*/
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct NatalPoint {
+  pub key: String,
+  pub lng: f64,
+  pub ra: f64,
+  pub dec: f64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct DirectedPoint {
+  pub key: String,
+  pub natal: f64,
+  pub lng: f64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct DirectionEvent {
+  pub method: String,
+  pub promissor: String,
+  pub significator: String,
+  pub aspect: String,
+  pub arc: f64,
+  pub years: f64,
+  pub jd: f64,
+  #[serde(skip_serializing_if = "String::is_empty")]
+  pub dt: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct DirectionSet {
+  pub method: String,
+  pub arc: f64,
+  #[serde(rename="yearLength")]
+  pub year_length: f64,
+  pub armc: f64,
+  pub positions: Vec<DirectedPoint>,
+  pub events: Vec<DirectionEvent>,
+}
+
+/*
+* Natal bodies followed by the ascendant and MC with the ARMC
+*/
+pub fn calc_natal_points(jd: f64, geo: GeoPos, keys: &Vec<&str>) -> (Vec<NatalPoint>, f64) {
+  let mut points: Vec<NatalPoint> = keys.iter().map(|key| {
+    let pos = calc_body_dual_jd_geo(jd, key, false, 0f64);
+    NatalPoint { key: key.to_string(), lng: pos.lng, ra: pos.rect_ascension, dec: pos.declination }
+  }).collect();
+  let hd = get_house_data(jd, geo.lat, geo.lng, 'W', false);
+  for (key, lng) in [("as", hd.points.ascendant), ("mc", hd.points.mc)] {
+    let eq = ecliptic_to_equatorial_basic(jd, lng, 0f64);
+    points.push(NatalPoint { key: key.to_string(), lng, ra: eq.lng, dec: eq.lat });
+  }
+  (points, hd.points.armc)
+}
+
+/*
+* Time when the solar or Naibod arc reaches the required value. The solar arc is solved via Newton's method
+* from the Naibod estimate, as the progressed Sun is never retrograde
+*/
+pub fn calc_arc_jd(natal_jd: f64, natal_sun_lng: f64, arc: f64, method: ProgressionMethod, year_length: f64) -> f64 {
+  let mut jd = natal_jd + arc / NAIBOD_ARC * year_length;
+  if method == ProgressionMethod::SolarArc {
+    for _ in 0..12 {
+      let sun = calc_body_jd_geo(natal_jd + (jd - natal_jd) / year_length, "su", 0f64);
+      let diff = normalize_180(arc - calc_mc_arc(method, natal_sun_lng, sun.lng, 0f64));
+      if diff.abs() < 0.000001f64 || sun.lng_speed <= 0f64 {
+        break;
+      }
+      jd += diff / sun.lng_speed * year_length;
+    }
+  }
+  jd
+}
+
+/*
+* Signed angles to be matched for each aspect type, e.g. 90º and 270º for squares
+*/
+fn to_aspect_angles(aspect_types: &Vec<AspectType>) -> Vec<(AspectType, f64)> {
+  let mut angles: Vec<(AspectType, f64)> = Vec::new();
+  for aspect in aspect_types {
+    let angle = aspect.angle();
+    angles.push((*aspect, angle));
+    if angle > 0f64 && angle < 180f64 {
+      angles.push((*aspect, 360f64 - angle));
+    }
+  }
+  angles
+}
+
+/*
+* Diurnal and nocturnal semi-arcs of a point with the referenced declination
+*/
+pub fn calc_semi_arcs(dec: f64, geo_lat: f64) -> (f64, f64) {
+  let ad = (geo_lat.to_radians().tan() * dec.to_radians().tan()).clamp(-1f64, 1f64).asin().to_degrees();
+  (90f64 + ad, 90f64 - ad)
+}
+
+/*
+* Placidus semi-arc direction: the rotation of the sphere in degrees until the promissor reaches
+* the same proportion of its semi-arc from the meridian as the significator. Hour angles increase westwards
+*/
+pub fn calc_placidus_arc(sig_ha: f64, sig_dec: f64, pro_ha: f64, pro_dec: f64, geo_lat: f64) -> f64 {
+  let (sig_dsa, sig_nsa) = calc_semi_arcs(sig_dec, geo_lat);
+  let (pro_dsa, pro_nsa) = calc_semi_arcs(pro_dec, geo_lat);
+  let ha = normalize_180(sig_ha);
+  let target_ha = if sig_dsa > 0f64 && ha.abs() <= sig_dsa {
+    ha / sig_dsa * pro_dsa
+  } else {
+    180f64 + normalize_180(ha - 180f64) / sig_nsa * pro_nsa
+  };
+  (target_ha - pro_ha).rem_euclid(360f64)
+}
+
+/*
+* Regiomontanus direction: the rotation of the sphere in degrees until the promissor reaches the position circle
+* of the significator, i.e. the great circle through the north and south points of the horizon and the significator
+*/
+pub fn calc_regiomontanus_arc(sig_ha: f64, sig_dec: f64, pro_ha: f64, pro_dec: f64, geo_lat: f64) -> f64 {
+  let phi = geo_lat.to_radians();
+  let sd = sig_dec.to_radians();
+  let sh = sig_ha.to_radians();
+  let theta = (-(phi.cos() * sd.cos() * sh.cos() + phi.sin() * sd.sin())).atan2(sd.cos() * sh.sin());
+  let r = (theta.cos().powi(2) * phi.cos().powi(2) + theta.sin().powi(2)).sqrt();
+  if r < 0.000000001f64 {
+    return 0f64;
+  }
+  let psi = theta.sin().atan2(theta.cos() * phi.cos()).to_degrees();
+  let offset = (-(theta.cos() * phi.sin() * pro_dec.to_radians().tan()) / r).clamp(-1f64, 1f64).acos().to_degrees();
+  let target_ha = [psi + offset, psi - offset].into_iter().min_by(|a, b| normalize_180(a - sig_ha).abs().partial_cmp(&normalize_180(b - sig_ha).abs()).unwrap()).unwrap();
+  (target_ha - pro_ha).rem_euclid(360f64)
+}
+
+fn to_direction_event(method: DirectionMethod, promissor: &str, significator: &str, aspect: AspectType, arc: f64, years: f64, jd: f64, iso_mode: bool) -> DirectionEvent {
+  let dt = if iso_mode { julian_day_to_iso_datetime(jd) } else { "".to_string() };
+  DirectionEvent {
+    method: method.as_key().to_string(),
+    promissor: promissor.to_string(),
+    significator: significator.to_string(),
+    aspect: aspect.as_key().to_string(),
+    arc,
+    years,
+    jd,
+    dt,
+  }
+}
+
+/*
+* Solar arc (or Naibod arc) directed positions at the target time and the times within the span
+* when directed points perfect aspects to natal points
+*/
+pub fn calc_solar_arc_directions(natal_jd: f64, points: &Vec<NatalPoint>, target_jd: f64, start_jd: f64, end_jd: f64, progression_method: ProgressionMethod, year_length: f64, aspect_types: &Vec<AspectType>, iso_mode: bool) -> (f64, Vec<DirectedPoint>, Vec<DirectionEvent>) {
+  let natal_sun_lng = calc_body_jd_geo(natal_jd, "su", 0f64).lng;
+  let calc_arc = |jd: f64| {
+    let years = (jd - natal_jd) / year_length;
+    let progressed_sun_lng = calc_body_jd_geo(natal_jd + years, "su", 0f64).lng;
+    calc_mc_arc(progression_method, natal_sun_lng, progressed_sun_lng, years)
+  };
+  let arc = calc_arc(target_jd);
+  let positions: Vec<DirectedPoint> = points.iter().map(|p| DirectedPoint { key: p.key.clone(), natal: p.lng, lng: (p.lng + arc).rem_euclid(360f64) }).collect();
+  let start_arc = calc_arc(start_jd);
+  let end_arc = calc_arc(end_jd);
+  let mut events: Vec<DirectionEvent> = Vec::new();
+  for directed in points {
+    for natal in points {
+      for (aspect, angle) in to_aspect_angles(aspect_types) {
+        if directed.key == natal.key && angle == 0f64 {
+          continue;
+        }
+        let required_arc = (natal.lng + angle - directed.lng).rem_euclid(360f64);
+        let in_range = if end_arc >= start_arc { required_arc >= start_arc && required_arc <= end_arc } else { required_arc >= start_arc || required_arc <= end_arc };
+        if in_range {
+          let jd = calc_arc_jd(natal_jd, natal_sun_lng, required_arc, progression_method, year_length);
+          let years = (jd - natal_jd) / year_length;
+          events.push(to_direction_event(DirectionMethod::SolarArc, &directed.key, &natal.key, aspect, required_arc, years, jd, iso_mode));
+        }
+      }
+    }
+  }
+  events.sort_by(|a, b| a.jd.partial_cmp(&b.jd).unwrap());
+  (arc, positions, events)
+}
+
+/*
+* Direct primary directions of the bodies and their zodiacal aspect points to all natal points within the span,
+* timed with the Naibod key
+*/
+pub fn calc_primary_directions(method: DirectionMethod, natal_jd: f64, points: &Vec<NatalPoint>, armc: f64, geo: GeoPos, start_jd: f64, end_jd: f64, year_length: f64, aspect_types: &Vec<AspectType>, iso_mode: bool) -> Vec<DirectionEvent> {
+  let mut events: Vec<DirectionEvent> = Vec::new();
+  let promissors: Vec<&NatalPoint> = points.iter().filter(|p| p.key != "as" && p.key != "mc").collect();
+  for promissor in promissors {
+    for (aspect, angle) in to_aspect_angles(aspect_types) {
+      let (pro_ra, pro_dec) = if angle == 0f64 {
+        (promissor.ra, promissor.dec)
+      } else {
+        let eq = ecliptic_to_equatorial_basic(natal_jd, promissor.lng + angle, 0f64);
+        (eq.lng, eq.lat)
+      };
+      for significator in points {
+        if significator.key == promissor.key && angle == 0f64 {
+          continue;
+        }
+        let sig_ha = armc - significator.ra;
+        let pro_ha = armc - pro_ra;
+        let arc = match method {
+          DirectionMethod::Regiomontanus => calc_regiomontanus_arc(sig_ha, significator.dec, pro_ha, pro_dec, geo.lat),
+          _ => calc_placidus_arc(sig_ha, significator.dec, pro_ha, pro_dec, geo.lat),
+        };
+        let years = arc / NAIBOD_ARC;
+        let jd = natal_jd + years * year_length;
+        if jd >= start_jd && jd <= end_jd {
+          events.push(to_direction_event(method, &promissor.key, &significator.key, aspect, arc, years, jd, iso_mode));
+        }
+      }
+    }
+  }
+  events.sort_by(|a, b| a.jd.partial_cmp(&b.jd).unwrap());
+  events
+}
+
+/*
+* Directed positions at the target time with the directions perfecting within the span for each method
+*/
+pub fn calc_directions(natal_jd: f64, geo: GeoPos, keys: Vec<&str>, target_jd: f64, end_jd: f64, methods: &Vec<DirectionMethod>, progression_method: ProgressionMethod, year_type: &str, aspect_types: &Vec<AspectType>, iso_mode: bool) -> Vec<DirectionSet> {
+  let year_length = get_year_length(year_type);
+  let (points, armc) = calc_natal_points(natal_jd, geo, &keys);
+  methods.iter().map(|method| {
+    match method {
+      DirectionMethod::SolarArc => {
+        let (arc, positions, events) = calc_solar_arc_directions(natal_jd, &points, target_jd, target_jd, end_jd, progression_method, year_length, aspect_types, iso_mode);
+        let method_key = match progression_method {
+          ProgressionMethod::Naibod => "naibod_arc",
+          _ => method.as_key(),
+        };
+        DirectionSet { method: method_key.to_string(), arc, year_length, armc, positions, events }
+      },
+      _ => {
+        let arc = (target_jd - natal_jd) / year_length * NAIBOD_ARC;
+        let events = calc_primary_directions(*method, natal_jd, &points, armc, geo, target_jd, end_jd, year_length, aspect_types, iso_mode);
+        DirectionSet { method: method.as_key().to_string(), arc, year_length, armc, positions: vec![], events }
+      }
+    }
+  }).collect()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn matches_primary_arcs_for_angles() {
+    let geo_lat = 45f64;
+    let pro_ha = -40f64;
+    let pro_dec = 10f64;
+    // to the MC both methods yield the right ascension difference
+    assert!((calc_placidus_arc(0f64, 15f64, pro_ha, pro_dec, geo_lat) - 40f64).abs() < 0.000001f64);
+    assert!((calc_regiomontanus_arc(0f64, 15f64, pro_ha, pro_dec, geo_lat) - 40f64).abs() < 0.000001f64);
+    // to the east point both methods yield the difference in oblique ascension
+    let (dsa, _) = calc_semi_arcs(pro_dec, geo_lat);
+    let expected = 150f64 - dsa;
+    assert!((calc_placidus_arc(-90f64, 0f64, -150f64, pro_dec, geo_lat) - expected).abs() < 0.000001f64);
+    assert!((calc_regiomontanus_arc(-90f64, 0f64, -150f64, pro_dec, geo_lat) - expected).abs() < 0.000001f64);
+    assert!(normalize_180(calc_regiomontanus_arc(-30f64, 5f64, -30f64, 5f64, geo_lat)).abs() < 0.000001f64);
+  }
+}
@@ -0,0 +1,203 @@
+use serde::{Serialize, Deserialize};
+use super::super::extensions::swe::{sol_eclipse_when_glob, sol_eclipse_when_loc, lun_eclipse_when};
+use super::core::{calc_altitude_object};
+use super::models::{geo_pos::GeoPos, general::{KeyNumValue, FlexiValue}};
+
+/*
+* Eclipse type flags as returned by the Swiss Ephemeris eclipse functions
+*/
+pub const ECL_CENTRAL: i32 = 1;
+pub const ECL_TOTAL: i32 = 4;
+pub const ECL_ANNULAR: i32 = 8;
+pub const ECL_PARTIAL: i32 = 16;
+pub const ECL_ANNULAR_TOTAL: i32 = 32;
+pub const ECL_PENUMBRAL: i32 = 64;
+pub const ECL_VISIBLE: i32 = 128;
+pub const ECL_MAX_VISIBLE: i32 = 256;
+
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq)]
+pub enum EclipseKind {
+  Solar,
+  Lunar,
+}
+
+impl EclipseKind {
+  pub fn as_key(&self) -> &str {
+    match self {
+      EclipseKind::Solar => "solar",
+      EclipseKind::Lunar => "lunar",
+    }
+  }
+}
+
+/*
+* Match eclipse kinds from a query string value, solar, lunar or both by default
+*/
+pub fn match_eclipse_kinds(ref_str: &str) -> Vec<EclipseKind> {
+  match ref_str.to_lowercase().as_str() {
+    "solar" | "sol" | "su" => vec![EclipseKind::Solar],
+    "lunar" | "lun" | "mo" => vec![EclipseKind::Lunar],
+    _ => vec![EclipseKind::Solar, EclipseKind::Lunar],
+  }
+}
+
+pub fn match_eclipse_type(flag: i32) -> String {
+  if flag & ECL_ANNULAR_TOTAL != 0 {
+    "hybrid"
+  } else if flag & ECL_TOTAL != 0 {
+    "total"
+  } else if flag & ECL_ANNULAR != 0 {
+    "annular"
+  } else if flag & ECL_PARTIAL != 0 {
+    "partial"
+  } else if flag & ECL_PENUMBRAL != 0 {
+    "penumbral"
+  } else {
+    "none"
+  }.to_string()
+}
+
+fn to_flexi_times(pairs: Vec<(&str, f64)>, iso_mode: bool) -> Vec<FlexiValue> {
+  pairs.into_iter().filter(|(_, jd)| *jd > 0f64).map(|(key, jd)| KeyNumValue::new(key, jd).as_flexi_value(iso_mode)).collect()
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct LocalEclipse {
+  pub visible: bool,
+  #[serde(rename="maxVisible")]
+  pub max_visible: bool,
+  #[serde(skip_serializing_if = "Vec::is_empty")]
+  pub times: Vec<FlexiValue>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub magnitude: Option<f64>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub obscuration: Option<f64>,
+  pub altitude: f64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct EclipseEvent {
+  pub kind: String,
+  #[serde(rename="type")]
+  pub eclipse_type: String,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub central: Option<bool>,
+  pub jd: f64,
+  pub times: Vec<FlexiValue>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub local: Option<LocalEclipse>,
+}
+
+/*
+* The solar eclipse nearest the global maximum as seen from the location, if visible there at all.
+* The local search may otherwise return a later eclipse, which is then ignored
+*/
+pub fn calc_local_solar_eclipse(max_jd: f64, geo: GeoPos, iso_mode: bool) -> LocalEclipse {
+  let (flag, tret, attr) = sol_eclipse_when_loc(max_jd - 1f64, geo.lat, geo.lng, geo.alt, false);
+  let matched = flag > 0 && (tret[0] - max_jd).abs() < 0.5f64;
+  match matched {
+    true => LocalEclipse {
+      visible: flag & ECL_VISIBLE != 0,
+      max_visible: flag & ECL_MAX_VISIBLE != 0,
+      times: to_flexi_times(vec![("first", tret[1]), ("second", tret[2]), ("max", tret[0]), ("third", tret[3]), ("fourth", tret[4])], iso_mode),
+      magnitude: Some(attr[8]),
+      obscuration: Some(attr[2]),
+      altitude: attr[5],
+    },
+    _ => LocalEclipse {
+      visible: false,
+      max_visible: false,
+      times: vec![],
+      magnitude: None,
+      obscuration: None,
+      altitude: calc_altitude_object(max_jd, false, geo.lat, geo.lng, "su"),
+    }
+  }
+}
+
+/*
+* Lunar eclipses are visible wherever the Moon is above the horizon at any phase of the eclipse
+*/
+pub fn calc_local_lunar_eclipse(max_jd: f64, contact_jds: Vec<f64>, geo: GeoPos) -> LocalEclipse {
+  let altitude = calc_altitude_object(max_jd, false, geo.lat, geo.lng, "mo");
+  let max_visible = altitude > 0f64;
+  let visible = max_visible || contact_jds.into_iter().filter(|jd| *jd > 0f64).any(|jd| calc_altitude_object(jd, false, geo.lat, geo.lng, "mo") > 0f64);
+  LocalEclipse { visible, max_visible, times: vec![], magnitude: None, obscuration: None, altitude }
+}
+
+/*
+* Next or previous eclipse of either kind from the reference time with its contact times
+*/
+pub fn calc_eclipse(jd: f64, kind: EclipseKind, geo_opt: Option<GeoPos>, backward: bool, iso_mode: bool) -> Option<EclipseEvent> {
+  let (flag, tret) = match kind {
+    EclipseKind::Solar => sol_eclipse_when_glob(jd, 0, backward),
+    EclipseKind::Lunar => lun_eclipse_when(jd, 0, backward),
+  };
+  if flag <= 0 || tret[0] <= 0f64 {
+    return None;
+  }
+  let times = match kind {
+    EclipseKind::Solar => to_flexi_times(vec![("begin", tret[2]), ("totalBegin", tret[4]), ("centerBegin", tret[6]), ("max", tret[0]), ("centerEnd", tret[7]), ("totalEnd", tret[5]), ("end", tret[3])], iso_mode),
+    EclipseKind::Lunar => to_flexi_times(vec![("penumbralBegin", tret[6]), ("partialBegin", tret[2]), ("totalBegin", tret[4]), ("max", tret[0]), ("totalEnd", tret[5]), ("partialEnd", tret[3]), ("penumbralEnd", tret[7])], iso_mode),
+  };
+  let local = match geo_opt {
+    Some(geo) => Some(match kind {
+      EclipseKind::Solar => calc_local_solar_eclipse(tret[0], geo, iso_mode),
+      EclipseKind::Lunar => calc_local_lunar_eclipse(tret[0], vec![tret[6], tret[2], tret[3], tret[7]], geo),
+    }),
+    _ => None,
+  };
+  Some(EclipseEvent {
+    kind: kind.as_key().to_string(),
+    eclipse_type: match_eclipse_type(flag),
+    central: if kind == EclipseKind::Solar { Some(flag & ECL_CENTRAL != 0) } else { None },
+    jd: tret[0],
+    times,
+    local,
+  })
+}
+
+/*
+* All eclipses of the referenced kinds with their maximum within the time span, sorted by time
+*/
+pub fn calc_eclipses_range(start_jd: f64, end_jd: f64, kinds: &Vec<EclipseKind>, geo_opt: Option<GeoPos>, iso_mode: bool) -> Vec<EclipseEvent> {
+  let mut items: Vec<EclipseEvent> = Vec::new();
+  for kind in kinds {
+    let mut ref_jd = start_jd;
+    while ref_jd < end_jd {
+      match calc_eclipse(ref_jd, *kind, geo_opt, false, iso_mode) {
+        Some(item) => {
+          if item.jd > end_jd {
+            break;
+          }
+          // consecutive eclipses of the same kind are at least one lunation apart
+          ref_jd = item.jd + 20f64;
+          items.push(item);
+        },
+        _ => break,
+      }
+    }
+  }
+  items.sort_by(|a, b| a.jd.partial_cmp(&b.jd).unwrap());
+  items
+}
+
+/*
+* The last eclipse of each referenced kind before the reference time
+*/
+pub fn calc_previous_eclipses(jd: f64, kinds: &Vec<EclipseKind>, geo_opt: Option<GeoPos>, iso_mode: bool) -> Vec<EclipseEvent> {
+  kinds.iter().filter_map(|kind| calc_eclipse(jd, *kind, geo_opt, true, iso_mode)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn matches_eclipse_types_from_flags() {
+    assert_eq!(match_eclipse_type(ECL_TOTAL | ECL_CENTRAL), "total");
+    assert_eq!(match_eclipse_type(ECL_ANNULAR_TOTAL | ECL_CENTRAL), "hybrid");
+    assert_eq!(match_eclipse_type(ECL_PARTIAL | ECL_VISIBLE), "partial");
+    assert_eq!(match_eclipse_type(ECL_PENUMBRAL), "penumbral");
+  }
+}
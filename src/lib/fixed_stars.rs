@@ -0,0 +1,192 @@
+use serde::{Serialize, Deserialize};
+use libswe_sys::sweconst::{Bodies};
+use super::super::extensions::swe::{fixstar2_ut, fixstar2_mag, rise_trans_star, Atmosphere, SweError};
+use super::{transitions::{TransitionParams, TransitionSet, TransitionGroup, calc_transition_set, start_jd_geo, to_event_jd}, traits::*, julian_date::{julian_day_to_iso_datetime}};
+use super::models::{geo_pos::GeoPos, general::{KeyNumValueSet, KeyStringValue}};
+use super::settings::{star_values::{DEFAULT_STARS}};
+
+/*
+* Rotation of the celestial sphere in degrees per day, used to express time differences between angle crossings as orbs
+*/
+pub const SIDEREAL_DEGREES_PER_DAY: f64 = 360.98564736629;
+
+pub const DEFAULT_PARAN_ORB: f64 = 1f64;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct FixedStarPos {
+  pub key: String,
+  pub name: String,
+  #[serde(skip_serializing_if = "String::is_empty")]
+  pub nomenclature: String,
+  pub lng: f64,
+  pub lat: f64,
+  #[serde(rename="lngSpeed")]
+  pub lng_speed: f64,
+  pub ra: f64,
+  pub dec: f64,
+  pub mag: f64,
+}
+
+/*
+* Match star names from a query string value, 1 or all for the default stars, otherwise comma-separated names
+*/
+pub fn match_star_names(ref_str: &str) -> Vec<String> {
+  match ref_str.trim().to_lowercase().as_str() {
+    "" | "0" => vec![],
+    "1" | "all" | "default" => DEFAULT_STARS.iter().map(|name| name.to_string()).collect(),
+    _ => ref_str.split(",").map(|name| name.trim().to_string()).filter(|name| name.len() > 0).collect(),
+  }
+}
+
+/*
+* Split the name returned by the Swiss Ephemeris into the traditional name and the Bayer / Flamsteed nomenclature
+*/
+fn split_star_name(full_name: &str) -> (String, String) {
+  match full_name.split_once(",") {
+    Some((name, nomenclature)) => (name.trim().to_string(), nomenclature.trim().to_string()),
+    _ => (full_name.trim().to_string(), "".to_string()),
+  }
+}
+
+/*
+* Ecliptic and equatorial coordinates of a star from sefstars.txt in the ephemeris path, with precession to the referenced time.
+* The Swiss Ephemeris error if the star or the catalogue is not found
+*/
+pub fn calc_star_position(jd: f64, star_name: &str, aya_offset: f64) -> Result<FixedStarPos, SweError> {
+  let (full_name, ecl) = fixstar2_ut(star_name, jd, 2i32 | 256i32)?; // SEFLG_SWIEPH | SEFLG_SPEED
+  let (_, eq) = fixstar2_ut(star_name, jd, 2i32 | 2048i32)?; // SEFLG_SWIEPH | SEFLG_EQUATORIAL
+  let mag = fixstar2_mag(star_name)?;
+  let (name, nomenclature) = split_star_name(full_name.as_str());
+  Ok(FixedStarPos {
+    key: star_name.to_string(),
+    name,
+    nomenclature,
+    lng: (ecl[0] - aya_offset).rem_euclid(360f64),
+    lat: ecl[1],
+    lng_speed: ecl[3],
+    ra: eq[0],
+    dec: eq[1],
+    mag,
+  })
+}
+
+/*
+* Positions of the matched stars with the Swiss Ephemeris errors of unknown stars by name
+*/
+pub fn calc_star_positions(jd: f64, star_names: &Vec<String>, aya_offset: f64) -> (Vec<FixedStarPos>, Vec<KeyStringValue>) {
+  let mut stars: Vec<FixedStarPos> = Vec::new();
+  let mut unknown_stars: Vec<KeyStringValue> = Vec::new();
+  for name in star_names {
+    match calc_star_position(jd, name.as_str(), aya_offset) {
+      Ok(star) => stars.push(star),
+      Err(error) => unknown_stars.push(KeyStringValue::new(name.as_str(), error.to_string().as_str())),
+    }
+  }
+  (stars, unknown_stars)
+}
+
+fn star_rise_trans(tjd_ut: f64, star_name: &str, geo: GeoPos, iflag: i32) -> Result<Option<f64>, SweError> {
+  to_event_jd(rise_trans_star(tjd_ut, star_name, geo, Atmosphere::default(), iflag)).map_err(|error| match error {
+    SweError::Failed(message) => SweError::Failed(format!("{}: {}", star_name, message)),
+    _ => error,
+  })
+}
+
+/*
+* Rise, culmination, set and lower culmination within the day of the referenced time, as with bodies.
+* Circumpolar stars and those that never rise only have culminations
+*/
+pub fn calc_star_transition_set(jd: f64, star_name: &str, geo: GeoPos) -> Result<TransitionSet, SweError> {
+  let ref_jd = start_jd_geo(jd, geo.lng);
+  let rise = star_rise_trans(ref_jd, star_name, geo, TransitionParams::center_disc_rising_rise())?;
+  let set = star_rise_trans(rise.unwrap_or(ref_jd).max(ref_jd), star_name, geo, TransitionParams::center_disc_rising_set())?;
+  let mc = star_rise_trans(ref_jd, star_name, geo, TransitionParams::mc())?;
+  let ic = star_rise_trans(ref_jd, star_name, geo, TransitionParams::ic())?;
+  Ok(TransitionSet {
+    rise,
+    mc,
+    set,
+    ic,
+  })
+}
+
+pub fn get_star_transition_sets(jd: f64, star_names: &Vec<String>, geo: GeoPos) -> Result<Vec<KeyNumValueSet>, SweError> {
+  star_names.iter().map(|name| calc_star_transition_set(jd, name.as_str(), geo).map(|tr_set| KeyNumValueSet::new(name.as_str(), tr_set.to_key_nums()))).collect()
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Paran {
+  pub star: String,
+  #[serde(rename="starAngle")]
+  pub star_angle: String,
+  pub body: String,
+  #[serde(rename="bodyAngle")]
+  pub body_angle: String,
+  #[serde(rename="starJd")]
+  pub star_jd: f64,
+  #[serde(rename="bodyJd")]
+  pub body_jd: f64,
+  #[serde(skip_serializing_if = "String::is_empty")]
+  pub dt: String,
+  pub orb: f64,
+}
+
+/*
+* Match parans, i.e. a star and a body crossing any of the four angles within the orb, expressed
+* in degrees of diurnal rotation, over the day at the referenced location
+*/
+pub fn match_parans(star_events: &Vec<KeyNumValueSet>, body_events: &Vec<KeyNumValueSet>, orb: f64, iso_mode: bool) -> Vec<Paran> {
+  let mut parans: Vec<Paran> = Vec::new();
+  for star_set in star_events {
+    for body_set in body_events {
+      for star_event in &star_set.items {
+        for body_event in &body_set.items {
+          let distance = (star_event.value - body_event.value).abs() * SIDEREAL_DEGREES_PER_DAY;
+          if distance <= orb {
+            let dt = if iso_mode { julian_day_to_iso_datetime(star_event.value) } else { "".to_string() };
+            parans.push(Paran {
+              star: star_set.key.clone(),
+              star_angle: star_event.key.clone(),
+              body: body_set.key.clone(),
+              body_angle: body_event.key.clone(),
+              star_jd: star_event.value,
+              body_jd: body_event.value,
+              dt,
+              orb: distance,
+            });
+          }
+        }
+      }
+    }
+  }
+  parans.sort_by(|a, b| a.star_jd.partial_cmp(&b.star_jd).unwrap());
+  parans
+}
+
+/*
+* Parans between the stars and bodies at the location within the day of the referenced time
+*/
+pub fn calc_parans(jd: f64, star_names: &Vec<String>, body_keys: &Vec<String>, geo: GeoPos, orb: f64, iso_mode: bool) -> Result<Vec<Paran>, SweError> {
+  let star_events = star_names.iter().map(|name| calc_star_transition_set(jd, name.as_str(), geo).map(|tr_set| KeyNumValueSet::new(name.as_str(), tr_set.to_events()))).collect::<Result<Vec<KeyNumValueSet>, SweError>>()?;
+  let body_events = body_keys.iter().map(|key| calc_transition_set(jd, Bodies::from_key(key.as_str()), geo, Atmosphere::default()).map(|tr_set| KeyNumValueSet::new(key.as_str(), tr_set.to_events()))).collect::<Result<Vec<KeyNumValueSet>, SweError>>()?;
+  Ok(match_parans(&star_events, &body_events, orb, iso_mode))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use super::super::models::general::{KeyNumValue};
+
+  #[test]
+  fn matches_parans_within_orb() {
+    let star_events = vec![KeyNumValueSet::new("Regulus", vec![KeyNumValue::new("rise", 2460000.25), KeyNumValue::new("mc", 2460000.5)])];
+    let body_events = vec![KeyNumValueSet::new("ma", vec![KeyNumValue::new("set", 2460000.2515), KeyNumValue::new("ic", 2460000.9)])];
+    let parans = match_parans(&star_events, &body_events, DEFAULT_PARAN_ORB, false);
+    assert_eq!(parans.len(), 1);
+    assert_eq!(parans[0].star_angle, "rise");
+    assert_eq!(parans[0].body_angle, "set");
+    assert!(parans[0].orb < 0.6f64);
+    assert_eq!(match_star_names("Spica, Vega"), vec!["Spica".to_string(), "Vega".to_string()]);
+    assert_eq!(split_star_name("Aldebaran,alTau"), ("Aldebaran".to_string(), "alTau".to_string()));
+  }
+}
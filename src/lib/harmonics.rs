@@ -0,0 +1,51 @@
+use serde::{Serialize, Deserialize};
+use super::{math_funcs::{calc_harmonic_value, subtract_360}};
+use super::models::{general::KeyNumValue};
+
+/*
+* Harmonic chart longitudes of all bodies and points for one harmonic number
+*/
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct HarmonicSet {
+  pub num: u16,
+  pub values: Vec<KeyNumValue>,
+}
+
+/*
+* Match harmonic numbers from a comma-separated list, e.g. 5,7,9, or a range such as 1-12
+*/
+pub fn match_harmonic_nums(ref_str: &str) -> Vec<u16> {
+  let mut nums: Vec<u16> = Vec::new();
+  for part in ref_str.split(",") {
+    let bounds: Vec<u16> = part.split("-").filter_map(|n| n.trim().parse::<u16>().ok()).collect();
+    let (start, end) = match bounds.len() {
+      1 => (bounds[0], bounds[0]),
+      2 => (bounds[0], bounds[1]),
+      _ => continue,
+    };
+    for num in start..=end.min(360) {
+      if num > 0 && !nums.contains(&num) {
+        nums.push(num);
+      }
+    }
+  }
+  nums
+}
+
+pub fn to_harmonic_sets(items: &Vec<(String, f64)>, aya_offset: f64, nums: &Vec<u16>) -> Vec<HarmonicSet> {
+  nums.iter().map(|num| HarmonicSet {
+    num: *num,
+    values: items.iter().map(|(key, lng)| KeyNumValue::new(key.as_str(), calc_harmonic_value(subtract_360(*lng, aya_offset), *num))).collect(),
+  }).collect()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn matches_harmonic_numbers() {
+    assert_eq!(match_harmonic_nums("5,7,9"), vec![5, 7, 9]);
+    assert_eq!(match_harmonic_nums("1-4,9,x"), vec![1, 2, 3, 4, 9]);
+  }
+}
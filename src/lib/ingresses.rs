@@ -0,0 +1,142 @@
+use serde::{Serialize, Deserialize};
+use super::{core::{calc_body_jd_geo, calc_body_jd_geo_sidereal}, aspect_transits::{sample_longitudes_with, match_crossings_with}, julian_date::{julian_day_to_iso_datetime}, math_funcs::{subtract_360}};
+use super::settings::{sign_values::{SIGN_NAMES}, nakshatra_values::{NAKSHATRA_NAMES_27}};
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum IngressMode {
+  Sign,
+  Nakshatra,
+  Degrees(Vec<f64>),
+}
+
+impl IngressMode {
+  pub fn as_key(&self) -> &str {
+    match self {
+      IngressMode::Sign => "sign",
+      IngressMode::Nakshatra => "nakshatra",
+      IngressMode::Degrees(_) => "degree",
+    }
+  }
+
+  /*
+  * Boundary longitudes to be crossed
+  */
+  pub fn targets(&self) -> Vec<f64> {
+    match self {
+      IngressMode::Sign => (0..12).map(|i| i as f64 * 30f64).collect(),
+      IngressMode::Nakshatra => (0..27).map(|i| i as f64 * 360f64 / 27f64).collect(),
+      IngressMode::Degrees(degs) => degs.iter().map(|d| subtract_360(*d, 0f64)).collect(),
+    }
+  }
+
+  /*
+  * One-based number and name of the sign or nakshatra entered when crossing the boundary.
+  * Retrograde bodies enter the preceding sign or nakshatra
+  */
+  pub fn match_entered(&self, target: f64, retro: bool) -> (u8, String) {
+    let (span, num_parts) = match self {
+      IngressMode::Sign => (30f64, 12),
+      IngressMode::Nakshatra => (360f64 / 27f64, 27),
+      IngressMode::Degrees(_) => return (0, "".to_string()),
+    };
+    let start_index = (target / span).round() as usize % num_parts;
+    let index = if retro { (start_index + num_parts - 1) % num_parts } else { start_index };
+    let name = match self {
+      IngressMode::Sign => SIGN_NAMES[index],
+      _ => NAKSHATRA_NAMES_27[index],
+    };
+    (index as u8 + 1, name.to_string())
+  }
+}
+
+/*
+* Match the ingress mode from a query string value: sign (default), nakshatra or comma-separated degrees
+*/
+pub fn match_ingress_mode(ref_str: &str) -> IngressMode {
+  match ref_str.to_lowercase().as_str() {
+    "nakshatra" | "nakshatras" | "nak" | "n" => IngressMode::Nakshatra,
+    "" | "sign" | "signs" | "s" => IngressMode::Sign,
+    _ => {
+      let degs: Vec<f64> = ref_str.split(",").filter_map(|d| d.trim().parse::<f64>().ok()).collect();
+      if degs.len() > 0 { IngressMode::Degrees(degs) } else { IngressMode::Sign }
+    }
+  }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct IngressEvent {
+  pub key: String,
+  pub mode: String,
+  pub lng: f64,
+  #[serde(skip_serializing_if = "is_zero")]
+  pub num: u8,
+  #[serde(skip_serializing_if = "String::is_empty")]
+  pub name: String,
+  pub retro: bool,
+  #[serde(rename="lngSpeed")]
+  pub lng_speed: f64,
+  pub jd: f64,
+  #[serde(skip_serializing_if = "String::is_empty")]
+  pub dt: String,
+}
+
+fn is_zero(num: &u8) -> bool {
+  *num == 0
+}
+
+/*
+* Tropical longitude or sidereal if an ayanamsha key is supplied. Ketu is always opposite Rahu
+*/
+fn calc_ingress_lng(jd: f64, key: &str, aya_key: &str) -> f64 {
+  match aya_key {
+    "" | "tropical" => calc_body_jd_geo(jd, key, 0f64).lng,
+    _ => calc_body_jd_geo_sidereal(jd, key, aya_key).lng,
+  }
+}
+
+/*
+* All sign, nakshatra or custom degree crossings of the referenced bodies within the time span, sorted by time.
+* Retrograde re-entries are included as the body may cross the same boundary three times around a station
+*/
+pub fn calc_ingresses(keys: Vec<&str>, mode: &IngressMode, aya_key: &str, start_jd: f64, end_jd: f64, iso_mode: bool) -> Vec<IngressEvent> {
+  let mut items: Vec<IngressEvent> = Vec::new();
+  let targets = mode.targets();
+  for key in keys {
+    let calc_lng = |jd: f64| calc_ingress_lng(jd, key, aya_key);
+    let samples = sample_longitudes_with(key, start_jd, end_jd, &calc_lng);
+    for target in targets.iter() {
+      for jd in match_crossings_with(&calc_lng, *target, &samples) {
+        let lng_speed = calc_body_jd_geo(jd, key, 0f64).lng_speed;
+        let retro = lng_speed < 0f64;
+        let (num, name) = mode.match_entered(*target, retro);
+        let dt = if iso_mode { julian_day_to_iso_datetime(jd) } else { "".to_string() };
+        items.push(IngressEvent {
+          key: key.to_string(),
+          mode: mode.as_key().to_string(),
+          lng: *target,
+          num,
+          name,
+          retro,
+          lng_speed,
+          jd,
+          dt,
+        });
+      }
+    }
+  }
+  items.sort_by(|a, b| a.jd.partial_cmp(&b.jd).unwrap());
+  items
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn matches_entered_sign_and_nakshatra() {
+    assert_eq!(IngressMode::Sign.match_entered(120f64, false), (5, "leo".to_string()));
+    assert_eq!(IngressMode::Sign.match_entered(0f64, true), (12, "pisces".to_string()));
+    assert_eq!(IngressMode::Nakshatra.match_entered(360f64 / 27f64 * 9f64, false).1, "magha");
+    assert_eq!(match_ingress_mode("15,45.5"), IngressMode::Degrees(vec![15f64, 45.5f64]));
+  }
+}
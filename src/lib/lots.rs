@@ -0,0 +1,192 @@
+use serde::{Serialize, Deserialize};
+use std::collections::HashMap;
+use super::{core::{calc_body_jd_geo, calc_body_dual_jd}, math_funcs::{calc_house_num}};
+use super::models::{geo_pos::GeoPos};
+use super::settings::{lot_values::{LOT_FORMULAS}, sign_values::{SIGN_NAMES}, graha_values::{match_body_num}};
+
+/*
+* Lot defined as A + B - C over body keys, angles (as, ds, mc, ic) or earlier lots.
+* With reverse, B and C are swapped in night charts
+*/
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct LotFormula {
+  pub key: String,
+  #[serde(default)]
+  pub name: String,
+  pub a: String,
+  pub b: String,
+  pub c: String,
+  #[serde(default)]
+  pub reverse: bool,
+}
+
+impl LotFormula {
+  pub fn new(key: &str, name: &str, a: &str, b: &str, c: &str, reverse: bool) -> LotFormula {
+    LotFormula { key: key.to_string(), name: name.to_string(), a: a.to_string(), b: b.to_string(), c: c.to_string(), reverse }
+  }
+
+  pub fn point_keys(&self) -> Vec<&str> {
+    vec![self.a.as_str(), self.b.as_str(), self.c.as_str()]
+  }
+
+  /*
+  * Lot and point keys in lower case to match body keys
+  */
+  pub fn to_lower_case(&self) -> LotFormula {
+    LotFormula { key: self.key.to_lowercase(), name: self.name.clone(), a: self.a.to_lowercase(), b: self.b.to_lowercase(), c: self.c.to_lowercase(), reverse: self.reverse }
+  }
+}
+
+pub fn default_lot_formulas() -> Vec<LotFormula> {
+  LOT_FORMULAS.iter().map(|(key, name, a, b, c)| LotFormula::new(key, name, a, b, c, true)).collect()
+}
+
+/*
+* Default formulas with custom formulas replacing those with the same key or appended in order
+*/
+pub fn merge_lot_formulas(custom: &Vec<LotFormula>) -> Vec<LotFormula> {
+  let mut formulas = default_lot_formulas();
+  for formula in custom {
+    match formulas.iter().position(|f| f.key == formula.key) {
+      Some(index) => formulas[index] = formula.clone(),
+      _ => formulas.push(formula.clone()),
+    }
+  }
+  formulas
+}
+
+/*
+* Formula points that are neither body keys, angles (as, ds, mc, ic) nor earlier lots
+*/
+pub fn match_unknown_lot_point_keys(formulas: &Vec<LotFormula>) -> Vec<String> {
+  let mut unknown_keys: Vec<String> = Vec::new();
+  for (index, formula) in formulas.iter().enumerate() {
+    for key in formula.point_keys() {
+      let is_known = ["as", "ds", "mc", "ic"].contains(&key) || formulas[..index].iter().any(|f| f.key == key) || match_body_num(key).is_some();
+      if !is_known && !unknown_keys.iter().any(|k| k == key) {
+        unknown_keys.push(key.to_string());
+      }
+    }
+  }
+  unknown_keys
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct LotPos {
+  pub key: String,
+  #[serde(skip_serializing_if = "String::is_empty")]
+  pub name: String,
+  pub lng: f64,
+  pub sign: u8,
+  #[serde(rename="signName")]
+  pub sign_name: String,
+  #[serde(skip_serializing_if = "is_zero")]
+  pub house: u8,
+  pub formula: String,
+  pub reversed: bool,
+}
+
+fn is_zero(num: &u8) -> bool {
+  *num == 0
+}
+
+/*
+* Charts are diurnal if the Sun is above the horizon
+*/
+pub fn is_day_chart(jd: f64, geo: GeoPos) -> bool {
+  calc_body_dual_jd(jd, "su", false, false, Some(geo), 0f64).altitude().unwrap_or(0f64) > 0f64
+}
+
+/*
+* Points referenced by the formulas with the supplied longitudes, angles opposite the ascendant and MC
+* and any other bodies calculated with the same ayanamsha offset
+*/
+pub fn to_lot_points(jd: f64, items: &Vec<(String, f64)>, formulas: &Vec<LotFormula>, aya_offset: f64) -> HashMap<String, f64> {
+  let mut points: HashMap<String, f64> = items.iter().map(|(k, v)| (k.clone(), *v)).collect();
+  for (key, opp_key) in [("as", "ds"), ("mc", "ic")] {
+    if let Some(lng) = points.get(key).map(|v| *v) {
+      points.insert(opp_key.to_string(), (lng + 180f64) % 360f64);
+    }
+  }
+  for formula in formulas {
+    for key in formula.point_keys() {
+      let is_lot = formulas.iter().any(|f| f.key == key);
+      if !is_lot && !points.contains_key(key) && match_body_num(key).is_some() {
+        points.insert(key.to_string(), calc_body_jd_geo(jd, key, aya_offset).lng);
+      }
+    }
+  }
+  points
+}
+
+/*
+* Lots in formula order, so each lot may be used by subsequent formulas. Lots with unknown points are skipped.
+* The house is matched from the cusps of the first house system if available
+*/
+pub fn calc_lots(formulas: &Vec<LotFormula>, points: &HashMap<String, f64>, is_day: bool, cusps: &Vec<f64>) -> Vec<LotPos> {
+  let mut values = points.clone();
+  let mut items: Vec<LotPos> = Vec::new();
+  for formula in formulas {
+    let reversed = formula.reverse && !is_day;
+    let (b, c) = if reversed { (&formula.c, &formula.b) } else { (&formula.b, &formula.c) };
+    if let (Some(va), Some(vb), Some(vc)) = (values.get(&formula.a), values.get(b), values.get(c)) {
+      let lng = (va + vb - vc).rem_euclid(360f64);
+      let sign_index = (lng / 30f64).floor() as usize % 12;
+      values.insert(formula.key.clone(), lng);
+      items.push(LotPos {
+        key: formula.key.clone(),
+        name: formula.name.clone(),
+        lng,
+        sign: sign_index as u8 + 1,
+        sign_name: SIGN_NAMES[sign_index].to_string(),
+        house: if cusps.len() == 12 { calc_house_num(lng, cusps) } else { 0 },
+        formula: format!("{} + {} - {}", formula.a, b, c),
+        reversed,
+      });
+    }
+  }
+  items
+}
+
+/*
+* Match lot keys from a query string value: 1 or all for all lots, otherwise comma-separated keys
+*/
+pub fn filter_lots(items: Vec<LotPos>, ref_str: &str) -> Vec<LotPos> {
+  match ref_str.to_lowercase().as_str() {
+    "" | "0" => vec![],
+    "1" | "all" => items,
+    keys_str => {
+      let keys: Vec<&str> = keys_str.split(",").map(|k| k.trim()).collect();
+      items.into_iter().filter(|lot| keys.contains(&lot.key.as_str())).collect()
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn reverses_lots_by_night() {
+    let points: HashMap<String, f64> = [("as", 100f64), ("su", 10f64), ("mo", 40f64), ("ve", 200f64)].iter().map(|(k, v)| (k.to_string(), *v)).collect();
+    let formulas = default_lot_formulas();
+    let cusps: Vec<f64> = (0..12).map(|i| 90f64 + i as f64 * 30f64).collect();
+    let day_lots = calc_lots(&formulas, &points, true, &cusps);
+    assert_eq!(day_lots[0].lng, 130f64);
+    assert_eq!(day_lots[0].house, 2);
+    assert_eq!(day_lots[1].lng, 70f64);
+    // eros = as + ve - spirit
+    assert_eq!(day_lots[2].lng, 230f64);
+    let night_lots = calc_lots(&formulas, &points, false, &cusps);
+    assert_eq!(night_lots[0].lng, 70f64);
+    assert!(night_lots[0].reversed);
+    assert_eq!(filter_lots(night_lots, "fortune,spirit").len(), 2);
+  }
+
+  #[test]
+  fn matches_unknown_lot_points() {
+    let custom = vec![LotFormula::new("test", "", "AS", "ZZ", "fortune", false).to_lower_case(), LotFormula::new("eros", "", "as", "ast:433", "ic", false)];
+    assert_eq!(match_unknown_lot_point_keys(&merge_lot_formulas(&custom)), vec!["zz"]);
+    assert!(match_unknown_lot_point_keys(&default_lot_formulas()).is_empty());
+  }
+}
@@ -1,6 +1,6 @@
-/* use super::models::{general::*, geo_pos::*, general::{LngLat}};
-use super::settings::varga_values::*;
-use super::utils::minmax::*; */
+use super::models::{geo_pos::{GeoPos}, general::{LngLat}};
+/* use super::utils::minmax::*; */
+use super::settings::varga_values::{TRIMSAMSA_ODD, TRIMSAMSA_EVEN};
 use super::julian_date::datetime_to_julian_day;
 
 pub fn get_year_length(year_type: &str) -> f64 {
@@ -22,13 +22,13 @@ pub fn to_progression_jd(
   source_jd + projected_duration
 }
 
-pub fn calc_progress_day_jds_by_year(source_jd: f64, start_year: u32, years: u16, per_year: u8) -> Vec<(f64, f64)> {
+pub fn calc_progress_day_jds_by_year(source_jd: f64, start_year: u32, years: u16, per_year: u8, year_type: &str) -> Vec<(f64, f64)> {
   let year_start_str = format!("{}-01-01T00:00:00", start_year);
   let start_jd = datetime_to_julian_day(year_start_str.as_str());
-  let start_p2_jd = to_progression_jd(source_jd, start_jd, "tropical");
+  let start_p2_jd = to_progression_jd(source_jd, start_jd, year_type);
   let mut items: Vec<(f64, f64)> = Vec::new();
   let interval = 1f64 / per_year as f64;
-  let interval_yr = get_year_length("tropical");
+  let interval_yr = get_year_length(year_type) * interval;
   let num_items = years as u32 * per_year as u32;
   for i in 0..num_items {
     let ref_jd = start_p2_jd + (interval * i as f64);
@@ -53,7 +53,98 @@ pub fn subtract_360(lng: f64, offset: f64) -> f64 {
   (lng + 360f64 - offset) % 360f64
 }
 
+/*
+* Zero-based index of one of 27 equal nakshatras of 13º 20' and the progress through it from 0 to 1
+*/
+pub fn calc_nakshatra_27(lng: f64) -> (u8, f64) {
+  let span = 360f64 / 27f64;
+  let ref_lng = subtract_360(lng, 0f64);
+  let index = (ref_lng / span).floor();
+  let progress = (ref_lng - index * span) / span;
+  (index as u8 % 27, progress)
+}
+
+
+
+/*
+* Zero-based sign index and progress through the unequal trimsamsa segment from 0 to 1
+*/
+fn calc_trimsamsa(deg: f64, is_odd: bool) -> (usize, f64) {
+  let segments = if is_odd { TRIMSAMSA_ODD } else { TRIMSAMSA_EVEN };
+  let mut start = 0f64;
+  for (end, sign) in segments {
+    if deg < end {
+      return (sign, (deg - start) / (end - start));
+    }
+    start = end;
+  }
+  (segments[4].1, 1f64)
+}
+
+/*
+* Zero-based varga sign index and progress through the division from 0 to 1 for a sidereal longitude
+* following the Parashari rules for each divisional chart. Odd signs are Aries, Gemini etc.
+* Other divisions are mapped cyclically from Aries, which matches the rules for D9 and D27
+*/
+pub fn calc_varga_sign_progress(lng: f64, num: u16) -> (usize, f64) {
+  let ref_lng = subtract_360(lng, 0f64);
+  let sign = (ref_lng / 30f64).floor() as usize % 12;
+  let deg = ref_lng - (sign as f64 * 30f64);
+  let is_odd = sign % 2 == 0;
+  let quality = sign % 3; // 0 movable, 1 fixed, 2 dual
+  if num == 30 {
+    return calc_trimsamsa(deg, is_odd);
+  }
+  let num_parts = if num > 0 { num as usize } else { 1 };
+  let part = 30f64 / num_parts as f64;
+  let index = ((deg / part).floor() as usize).min(num_parts - 1);
+  let progress = (deg - index as f64 * part) / part;
+  let varga_sign = match num {
+    2 => if is_odd { 4 - index } else { 3 + index },
+    3 => sign + index * 4,
+    4 => sign + index * 3,
+    7 => if is_odd { sign + index } else { sign + 6 + index },
+    10 => if is_odd { sign + index } else { sign + 8 + index },
+    12 => sign + index,
+    16 | 45 => [0, 4, 8][quality] + index,
+    20 => [0, 8, 4][quality] + index,
+    24 => if is_odd { 4 + index } else { 3 + index },
+    40 => if is_odd { index } else { 6 + index },
+    60 => sign + index,
+    _ => sign * num_parts + index,
+  };
+  (varga_sign % 12, progress)
+}
+
+/*
+* Longitude in a divisional chart with the varga sign and the progress through the division mapped to 30º
+*/
+pub fn calc_varga_value(lng: f64, num: u16) -> f64 {
+  let (sign, progress) = calc_varga_sign_progress(lng, num);
+  sign as f64 * 30f64 + progress * 30f64
+}
+
+/*
+* Longitude in a harmonic chart, where each division of 360º / num is expanded to the full circle.
+* This matches the cyclic varga mapping from Aries, e.g. for D9 and D27
+*/
+pub fn calc_harmonic_value(lng: f64, num: u16) -> f64 {
+  (subtract_360(lng, 0f64) * num as f64).rem_euclid(360f64)
+}
 
+/*
+* One-based house number of a longitude from house cusps in zodiacal order
+*/
+pub fn calc_house_num(lng: f64, cusps: &Vec<f64>) -> u8 {
+  let num_cusps = cusps.len();
+  for i in 0..num_cusps {
+    let span = subtract_360(cusps[(i + 1) % num_cusps], cusps[i]);
+    if subtract_360(lng, cusps[i]) < span {
+      return i as u8 + 1;
+    }
+  }
+  1
+}
 
 pub fn recalc_houses(positions: Vec<f64>, ayanamsha: f64, ascendant: Option<f64>, system: Option<char>) -> Vec<f64> {
   let is_whole = match system {
@@ -123,22 +214,6 @@ pub fn limit_value_to_range(num: f64, min: f64, max: f64) -> f64 {
   if min < 0f64 && (val < 0f64 || num > max) { 0f64 - out_val } else { out_val }
 }
 
-pub fn calc_varga_value(lng: f64, num: u16) -> f64 {
-  (lng * num as f64) % 360f64
-}
-
-pub fn calc_all_vargas(lng: f64) -> Vec<NumValue> {
-  all_varga_items().into_iter().map(|v| {
-    let value = calc_varga_value(lng, v.num);
-    NumValue::new(v.num, value)
-  }).collect()
-}
-
-pub fn calc_varga_set(lng: f64, num: u16, key: &str) -> NumValueKeySet {
-  let values = calc_all_vargas(lng);
-  NumValueKeySet::new(num, key, values)
-}
-
 pub fn calc_inclusive_distance(
   pos_1: u16,
   pos_2: u16,
@@ -160,35 +235,26 @@ pub fn calc_inclusive_nakshatras(pos_1: u8, pos_2: u8) -> u8 {
   calc_inclusive_twelfths(pos_1 as u16, pos_2 as u16) as u8
 }
 
-pub fn to_360(lng: f64) -> f64 {
-  if lng >= 0f64 { lng + 180f64 }  else { 180f64 - lng }
-}
+*/
 
-pub fn from_360(lng: f64) -> f64 {
-  if lng > 180f64 { lng - 180f64 } else { 0f64 - (180f64 - lng) }
+/*
+* Midpoint of two ecliptic longitudes on the shorter arc between them
+*/
+pub fn calc_mid_lng(lng1: f64, lng2: f64) -> f64 {
+  let diff = (lng2 - lng1 + 540f64).rem_euclid(360f64) - 180f64;
+  (lng1 + diff / 2f64).rem_euclid(360f64)
 }
 
 pub fn median_lat(v1: f64, v2: f64) -> f64 {
-  let offset = 90f64;
-  return (v1 + offset + (v2 + offset)) / 2f64 - offset;
+  (v1 + v2) / 2f64
 }
 
+/*
+* Median of two geographic longitudes from -180º to 180º across the shorter arc, so the median
+* of 170º E and 170º W lies on the date line rather than the prime meridian
+*/
 pub fn median_lng(v1: f64, v2: f64) -> f64 {
-  let offset = 180f64;
-  let full_circle = offset * 2f64;
-  let d1 = to_360(v1);
-  let d2 = to_360(v2);
-  let reverse = (d2 - d1).abs() > offset;
-  let is_west = if reverse { v1 + v2 > 0f64 } else { v1 + v2 < 0f64 };
-  let res360 = ((d1 + d2) % full_circle) / 2f64;
-  let res_a = from_360(res360) % offset;
-  let res1 = if is_west { res_a } else { 0f64 - res_a };
-  let res_b = offset - res1;
-  let res2a = if is_west  { full_circle - res_b } else { res_b };
-  let res2 = if is_west && res2a > 0f64  { 0f64 - res2a } else { res2a };
-  let res2_is_between = to_360(res2) > d1 && to_360(res2) <= d2;
-  let result = if reverse || res2_is_between  { res2 } else { res1 };
-  result
+  calc_mid_lng(v1 + 180f64, v2 + 180f64) - 180f64
 }
 
 pub fn median_lat_lng(coord1: GeoPos, coord2: GeoPos) -> LngLat {
@@ -197,18 +263,65 @@ pub fn median_lat_lng(coord1: GeoPos, coord2: GeoPos) -> LngLat {
     median_lat(coord1.lat, coord2.lat),
   )
 }
- */
-
-/* pub fn mid_point_to_surface(coord1: GeoPos, coord2: GeoPos) -> GeoPos {
-  let c1 = geo_to_radians(coord1);
-  let c2 = geo_to_radians(coord2);
-  let bx = Math.cos(c2.lat) * Math.cos(c2.lng - c1.lng);
-  let by = Math.cos(c2.lat) * Math.sin(c2.lng - c1.lng);
-  let mid_lat = Math.atan2(
-    Math.sin(c1.lat) + Math.sin(c2.lat),
-    Math.sqrt((Math.cos(c1.lat) + bx) * (Math.cos(c1.lat) + bx) + by * by),
-  );
-  let mid_lng = c1.lng + Math.atan2(by, Math.cos(c1.lat) + bx);
-  let mid_alt = (c1.alt + c2.alt) / 2f64;
-  GeoPos::new( to_degrees(mid_lat), lng: to_degrees(mid_lng), mid_alt)
-} */
\ No newline at end of file
+
+/*
+* Midpoint of the great circle between two locations on the Earth's surface with the mean altitude
+*/
+pub fn mid_point_to_surface(coord1: GeoPos, coord2: GeoPos) -> GeoPos {
+  let (lat1, lng1) = (coord1.lat.to_radians(), coord1.lng.to_radians());
+  let (lat2, lng2) = (coord2.lat.to_radians(), coord2.lng.to_radians());
+  let bx = lat2.cos() * (lng2 - lng1).cos();
+  let by = lat2.cos() * (lng2 - lng1).sin();
+  let mid_lat = (lat1.sin() + lat2.sin()).atan2(((lat1.cos() + bx) * (lat1.cos() + bx) + by * by).sqrt());
+  let mid_lng = lng1 + by.atan2(lat1.cos() + bx);
+  let mid_alt = (coord1.alt + coord2.alt) / 2f64;
+  GeoPos::new(mid_lat.to_degrees(), (mid_lng.to_degrees() + 540f64).rem_euclid(360f64) - 180f64, mid_alt)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn matches_harmonics_with_cyclic_vargas() {
+    assert!((calc_harmonic_value(130f64, 9) - calc_varga_value(130f64, 9)).abs() < 0.000001f64);
+    assert!((calc_harmonic_value(50f64, 27) - calc_varga_value(50f64, 27)).abs() < 0.000001f64);
+    assert!((calc_harmonic_value(350f64, 5) - 310f64).abs() < 0.000001f64);
+  }
+
+  #[test]
+  fn calculates_midpoints_across_zero() {
+    assert!((calc_mid_lng(350f64, 20f64) - 5f64).abs() < 0.000001f64);
+    assert!((calc_mid_lng(20f64, 350f64) - 5f64).abs() < 0.000001f64);
+    assert!((median_lng(170f64, -170f64).abs() - 180f64).abs() < 0.000001f64);
+    assert!((median_lng(-10f64, 30f64) - 10f64).abs() < 0.000001f64);
+    let mid = mid_point_to_surface(GeoPos::new(0f64, -10f64, 0f64), GeoPos::new(0f64, 30f64, 100f64));
+    assert!(mid.lat.abs() < 0.000001f64 && (mid.lng - 10f64).abs() < 0.000001f64 && mid.alt == 50f64);
+  }
+
+  #[test]
+  fn matches_varga_signs_in_odd_signs() {
+    // 10º Leo
+    let lng = 130f64;
+    assert_eq!(calc_varga_sign_progress(lng, 1).0, 4);
+    assert_eq!(calc_varga_sign_progress(lng, 2).0, 4);
+    assert_eq!(calc_varga_sign_progress(lng, 3).0, 8);
+    assert_eq!(calc_varga_sign_progress(lng, 9).0, 3);
+    assert_eq!(calc_varga_sign_progress(lng, 30).0, 8);
+    assert_eq!(calc_varga_sign_progress(lng, 60).0, 0);
+  }
+
+  #[test]
+  fn matches_varga_signs_in_even_signs() {
+    // 20º Taurus
+    let lng = 50f64;
+    assert_eq!(calc_varga_sign_progress(lng, 2).0, 4);
+    assert_eq!(calc_varga_sign_progress(lng, 7).0, 11);
+    assert_eq!(calc_varga_sign_progress(lng, 10).0, 3);
+    // the dwadasamsa counts from the sign itself, 9th division of Taurus is Capricorn
+    assert_eq!(calc_varga_sign_progress(lng, 12).0, 9);
+    assert_eq!(calc_varga_sign_progress(lng, 24).0, 7);
+    assert_eq!(calc_varga_sign_progress(lng, 30).0, 9);
+    assert_eq!(calc_varga_sign_progress(lng, 9).0, (lng * 9f64 / 30f64).floor() as usize % 12);
+  }
+}
@@ -0,0 +1,102 @@
+use serde::{Serialize, Deserialize};
+use super::{math_funcs::{calc_mid_lng}};
+use super::models::{graha_pos::GrahaPos};
+
+pub const DEFAULT_MIDPOINT_ORB: f64 = 1.5;
+
+/*
+* Position on a dial of 360º, 90º, 45º or 22.5º, where all hard aspects coincide on the 90º dial
+*/
+pub fn to_dial_position(lng: f64, dial: f64) -> f64 {
+  lng.rem_euclid(dial)
+}
+
+/*
+* Signed distance between two positions on the dial from -dial/2 to +dial/2
+*/
+pub fn calc_dial_distance(pos1: f64, pos2: f64, dial: f64) -> f64 {
+  (pos1 - pos2 + dial / 2f64).rem_euclid(dial) - dial / 2f64
+}
+
+/*
+* Body or midpoint within orb of a point on the dial with the signed orb
+*/
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct MidpointHit {
+  pub key: String,
+  pub orb: f64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Midpoint {
+  pub key1: String,
+  pub key2: String,
+  pub lng: f64,
+  pub dial: f64,
+  #[serde(skip_serializing_if = "Vec::is_empty")]
+  pub hits: Vec<MidpointHit>,
+}
+
+/*
+* Midpoint tree of one body with all midpoints within orb on the dial
+*/
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct MidpointTree {
+  pub key: String,
+  pub lng: f64,
+  pub dial: f64,
+  pub midpoints: Vec<MidpointHit>,
+}
+
+/*
+* All pairwise nearer midpoints sorted by their position on the dial, with the bodies falling on them within the orb
+*/
+pub fn calc_midpoints(positions: &Vec<GrahaPos>, dial: f64, orb: f64) -> Vec<Midpoint> {
+  let mut items: Vec<Midpoint> = Vec::new();
+  let num_positions = positions.len();
+  for i in 0..num_positions {
+    for j in (i + 1)..num_positions {
+      let (p1, p2) = (&positions[i], &positions[j]);
+      let lng = calc_mid_lng(p1.lng, p2.lng);
+      let dial_pos = to_dial_position(lng, dial);
+      let hits: Vec<MidpointHit> = positions.iter().filter(|p| p.key != p1.key && p.key != p2.key).filter_map(|p| {
+        let distance = calc_dial_distance(to_dial_position(p.lng, dial), dial_pos, dial);
+        if distance.abs() <= orb { Some(MidpointHit { key: p.key.clone(), orb: distance }) } else { None }
+      }).collect();
+      items.push(Midpoint { key1: p1.key.clone(), key2: p2.key.clone(), lng, dial: dial_pos, hits });
+    }
+  }
+  items.sort_by(|a, b| a.dial.partial_cmp(&b.dial).unwrap());
+  items
+}
+
+/*
+* Midpoint trees for each body from the midpoints matched above
+*/
+pub fn to_midpoint_trees(positions: &Vec<GrahaPos>, midpoints: &Vec<Midpoint>, dial: f64) -> Vec<MidpointTree> {
+  positions.iter().map(|p| {
+    let mut tree_items: Vec<MidpointHit> = midpoints.iter().filter_map(|mp| {
+      mp.hits.iter().find(|h| h.key == p.key).map(|h| MidpointHit { key: format!("{}/{}", mp.key1, mp.key2), orb: h.orb })
+    }).collect();
+    tree_items.sort_by(|a, b| a.orb.abs().partial_cmp(&b.orb.abs()).unwrap());
+    MidpointTree { key: p.key.clone(), lng: p.lng, dial: to_dial_position(p.lng, dial), midpoints: tree_items }
+  }).collect()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn matches_bodies_on_midpoints() {
+    assert!((calc_dial_distance(89f64, 1f64, 90f64) + 2f64).abs() < 0.000001f64);
+    let positions = vec![GrahaPos::basic("su", 350f64), GrahaPos::basic("mo", 30f64), GrahaPos::basic("ma", 101f64)];
+    let midpoints = calc_midpoints(&positions, 90f64, 1.5f64);
+    let su_mo = midpoints.iter().find(|mp| mp.key1 == "su" && mp.key2 == "mo").unwrap();
+    assert!((su_mo.lng - 10f64).abs() < 0.000001f64);
+    assert_eq!(su_mo.hits.len(), 1);
+    assert!((su_mo.hits[0].orb - 1f64).abs() < 0.000001f64);
+    let trees = to_midpoint_trees(&positions, &midpoints, 90f64);
+    assert_eq!(trees[2].midpoints[0].key, "su/mo");
+  }
+}
@@ -8,4 +8,20 @@ pub mod models;
 pub mod utils;
 pub mod traits;
 pub mod math_funcs;
-pub mod planet_stations;
\ No newline at end of file
+pub mod planet_stations;
+pub mod aspects;
+pub mod aspect_transits;
+pub mod dasha;
+pub mod panchanga;
+pub mod eclipses;
+pub mod moon_phases;
+pub mod ingresses;
+pub mod progressions;
+pub mod directions;
+pub mod returns;
+pub mod composite;
+pub mod midpoints;
+pub mod harmonics;
+pub mod lots;
+pub mod fixed_stars;
+pub mod sky;
\ No newline at end of file
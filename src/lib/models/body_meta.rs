@@ -0,0 +1,95 @@
+use serde::{Serialize, Deserialize};
+use super::super::{math_funcs::{calc_nakshatra_27, subtract_360}, settings::{nakshatra_values::*, sign_values::*}};
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct BodyMeta {
+  pub key: String,
+  pub sign: u8,
+  #[serde(rename="signName")]
+  pub sign_name: String,
+  #[serde(rename="signDegree")]
+  pub sign_degree: f64,
+  #[serde(rename="signLord")]
+  pub sign_lord: String,
+  pub nakshatra: u8,
+  #[serde(rename="nakshatraName")]
+  pub nakshatra_name: String,
+  #[serde(rename="nakshatraLord",skip_serializing_if = "String::is_empty")]
+  pub nakshatra_lord: String,
+  pub pada: u8,
+}
+
+impl BodyMeta {
+
+  /*
+  * The sign is matched from the longitude as shown, which is only sidereal if an ayanamsha has already been applied.
+  * Nakshatras and padas are always sidereal, hence the nakshatra offset is the ayanamsha not yet applied to the longitude
+  */
+  pub fn new(key: &str, lng: f64, nakshatra_offset: f64, num_nakshatras: u8) -> BodyMeta {
+    let ref_lng = subtract_360(lng, 0f64);
+    let sign_index = (ref_lng / 30f64).floor() as usize % 12;
+    let sid_lng = subtract_360(ref_lng, nakshatra_offset);
+    let (nakshatra_index, progress) = calc_nakshatra_27(sid_lng);
+    let pada = (progress * 4f64).floor() as u8 + 1;
+    let (nakshatra_num, nakshatra_name, nakshatra_lord) = match num_nakshatras {
+      28 => match_nakshatra_28(sid_lng, nakshatra_index),
+      _ => (nakshatra_index + 1, NAKSHATRA_NAMES_27[nakshatra_index as usize], NAKSHATRA_LORDS[nakshatra_index as usize % 9]),
+    };
+    BodyMeta {
+      key: key.to_string(),
+      sign: sign_index as u8 + 1,
+      sign_name: SIGN_NAMES[sign_index].to_string(),
+      sign_degree: ref_lng % 30f64,
+      sign_lord: SIGN_LORDS[sign_index].to_string(),
+      nakshatra: nakshatra_num,
+      nakshatra_name: nakshatra_name.to_string(),
+      nakshatra_lord: nakshatra_lord.to_string(),
+      pada: if pada > 4 { 4 } else { pada },
+    }
+  }
+}
+
+/*
+* Number, name and lord in the 28 nakshatra system. Abhijit has no vimshottari lord
+*/
+fn match_nakshatra_28(sid_lng: f64, index_27: u8) -> (u8, &'static str, &'static str) {
+  let lord = NAKSHATRA_LORDS[index_27 as usize % 9];
+  if sid_lng < ABHIJIT_START {
+    (index_27 + 1, NAKSHATRA_NAMES_27[index_27 as usize], lord)
+  } else if sid_lng < ABHIJIT_END {
+    (ABHIJIT_INDEX + 1, "abhijit", "")
+  } else {
+    (index_27 + 2, NAKSHATRA_NAMES_27[index_27 as usize], lord)
+  }
+}
+
+pub fn to_body_meta_items(items: Vec<(String, f64)>, nakshatra_offset: f64, num_nakshatras: u8) -> Vec<BodyMeta> {
+  items.into_iter().map(|(key, lng)| BodyMeta::new(key.as_str(), lng, nakshatra_offset, num_nakshatras)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::BodyMeta;
+
+  #[test]
+  fn matches_sign_nakshatra_and_pada() {
+    // 10º Leo sidereal is in the 4th pada of Magha
+    let meta = BodyMeta::new("su", 130f64, 0f64, 27);
+    assert_eq!(meta.sign, 5);
+    assert_eq!(meta.sign_lord, "su");
+    assert_eq!(meta.nakshatra, 10);
+    assert_eq!(meta.nakshatra_lord, "ke");
+    assert_eq!(meta.pada, 4);
+    // the same tropical longitude with a 24º ayanamsha falls in Pushya
+    let meta_trop = BodyMeta::new("su", 130f64, 24f64, 27);
+    assert_eq!(meta_trop.sign, 5);
+    assert_eq!(meta_trop.nakshatra_name, "pushya");
+  }
+
+  #[test]
+  fn inserts_abhijit_in_28_nakshatra_mode() {
+    assert_eq!(BodyMeta::new("mo", 278f64, 0f64, 28).nakshatra_name, "abhijit");
+    assert_eq!(BodyMeta::new("mo", 285f64, 0f64, 28).nakshatra, 23);
+    assert_eq!(BodyMeta::new("mo", 285f64, 0f64, 27).nakshatra, 22);
+  }
+}
@@ -112,6 +112,23 @@ impl PhenoItem {
   }
 }
 
+/*
+* Horizontal position of a celestial object for an observer. Airmass only applies above the horizon
+*/
+#[derive(Serialize, Deserialize, Debug, Copy, Clone)]
+pub struct SkyPos {
+  pub altitude: f64,
+  #[serde(rename="apparentAltitude")]
+  pub apparent_altitude: f64,
+  pub azimuth: f64,
+  #[serde(rename="hourAngle")]
+  pub hour_angle: f64,
+  #[serde(rename="parallacticAngle")]
+  pub parallactic_angle: f64,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub airmass: Option<f64>,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct GrahaPos {
   pub key: String,
@@ -133,7 +150,15 @@ pub struct GrahaPos {
   #[serde(skip_serializing_if = "Option::is_none")]
   altitude: Option<f64>,
   #[serde(skip_serializing_if = "Option::is_none")]
-  azimuth: Option<f64>
+  azimuth: Option<f64>,
+  #[serde(rename="apparentAltitude", skip_serializing_if = "Option::is_none")]
+  apparent_altitude: Option<f64>,
+  #[serde(rename="hourAngle", skip_serializing_if = "Option::is_none")]
+  hour_angle: Option<f64>,
+  #[serde(rename="parallacticAngle", skip_serializing_if = "Option::is_none")]
+  parallactic_angle: Option<f64>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  airmass: Option<f64>,
 }
 
 impl GrahaPos {
@@ -155,7 +180,11 @@ impl GrahaPos {
       lat_speed_eq: 0f64,
       pheno: None,
       altitude: None,
-      azimuth: None
+      azimuth: None,
+      apparent_altitude: None,
+      hour_angle: None,
+      parallactic_angle: None,
+      airmass: None,
     }
   }
 
@@ -176,7 +205,11 @@ impl GrahaPos {
       lat_speed_eq: lat_speed,
       pheno: None,
       altitude: None,
-      azimuth: None
+      azimuth: None,
+      apparent_altitude: None,
+      hour_angle: None,
+      parallactic_angle: None,
+      airmass: None,
     }
   }
 
@@ -197,7 +230,11 @@ impl GrahaPos {
       lat_speed_eq,
       pheno: None,
       altitude: None,
-      azimuth: None
+      azimuth: None,
+      apparent_altitude: None,
+      hour_angle: None,
+      parallactic_angle: None,
+      airmass: None,
     }
   }
 
@@ -214,7 +251,11 @@ impl GrahaPos {
       lat_speed_eq,
       pheno,
       altitude,
-      azimuth
+      azimuth,
+      apparent_altitude: None,
+      hour_angle: None,
+      parallactic_angle: None,
+      airmass: None,
     }
   }
 
@@ -234,7 +275,11 @@ impl GrahaPos {
       lat_speed_eq: 0f64,
       pheno: None,
       altitude: None,
-      azimuth: None
+      azimuth: None,
+      apparent_altitude: None,
+      hour_angle: None,
+      parallactic_angle: None,
+      airmass: None,
     }
   }
 
@@ -255,7 +300,11 @@ impl GrahaPos {
       lat_speed_eq: 0f64,
       pheno: None,
       altitude: None,
-      azimuth: None
+      azimuth: None,
+      apparent_altitude: None,
+      hour_angle: None,
+      parallactic_angle: None,
+      airmass: None,
     }
   }
 
@@ -275,7 +324,34 @@ impl GrahaPos {
       lat_speed_eq: 0f64,
       pheno: None,
       altitude: None,
-      azimuth: None
+      azimuth: None,
+      apparent_altitude: None,
+      hour_angle: None,
+      parallactic_angle: None,
+      airmass: None,
+    }
+  }
+
+  pub fn altitude(&self) -> Option<f64> {
+    self.altitude
+  }
+
+  pub fn apparent_altitude(&self) -> Option<f64> {
+    self.apparent_altitude
+  }
+
+  /**
+   * Add the apparent altitude with refraction, hour angle, parallactic angle and airmass as seen by the observer
+   */
+  pub fn with_sky_position(&self, sky: SkyPos) -> Self {
+    GrahaPos {
+      altitude: Some(sky.altitude),
+      azimuth: Some(sky.azimuth),
+      apparent_altitude: Some(sky.apparent_altitude),
+      hour_angle: Some(sky.hour_angle),
+      parallactic_angle: Some(sky.parallactic_angle),
+      airmass: sky.airmass,
+      ..self.to_owned()
     }
   }
 
@@ -1,18 +1,23 @@
 use serde::{Serialize, Deserialize};
 use libswe_sys::swerust::{handler_swe14::*};
 use super::{geo_pos::*};
-use super::super::{core::{calc_altitude_tuple, ecliptic_to_equatorial_tuple}, math_funcs::{recalc_houses_by_system, subtract_360}};
+use super::super::{core::{calc_altitude_tuple, ecliptic_to_equatorial_tuple, ecliptic_to_equatorial_basic}, math_funcs::{recalc_houses_by_system, subtract_360}, aspect_transits::{normalize_180}};
+
+/*
+* Mean sidereal rotation of the Earth in degrees per solar day
+*/
+const EARTH_ROTATION_PER_DAY: f64 = 360.98564736629;
 
 #[derive(Serialize, Deserialize, Clone, Copy, Debug)]
 pub struct AscMc {
-  ascendant: f64,
-  mc: f64,
-  armc: f64,
-  vertex: f64,
-  equasc: f64,		// "equatorial ascendant" *
-  coasc1: f64,		// "co-ascendant" (W. Koch) *
-  coasc2: f64,		// "co-ascendant" (M. Munkasey) *
-  polasc: f64,
+  pub ascendant: f64,
+  pub mc: f64,
+  pub armc: f64,
+  pub vertex: f64,
+  pub equasc: f64,		// "equatorial ascendant" *
+  pub coasc1: f64,		// "co-ascendant" (W. Koch) *
+  pub coasc2: f64,		// "co-ascendant" (M. Munkasey) *
+  pub polasc: f64,
   #[serde(rename="ascAzi",skip_serializing_if = "Option::is_none")]
   asc_azi: Option<f64>,
   #[serde(rename="ascRa",skip_serializing_if = "Option::is_none")]
@@ -72,7 +77,7 @@ impl AscMc {
 
   pub fn apply_ayanamsha(&mut self, aya_offset: f64) {
     self.ascendant = subtract_360(self.ascendant, aya_offset);
-    self.mc = subtract_360(self.ascendant, aya_offset);
+    self.mc = subtract_360(self.mc, aya_offset);
   }
 
 }
@@ -194,6 +199,17 @@ pub fn get_house_systems(jd: f64, geo: GeoPos, keys: Vec<char>, aya_offset: f64)
   HouseSetData::new(points, sets, aya_offset)
 }
 
+/*
+* House cusps for a derived MC, e.g. progressed or composite, at the referenced location. The houses are cast for the time
+* closest to the reference time when the local sidereal time matches the right ascension of the MC
+*/
+pub fn get_house_systems_for_mc(ref_jd: f64, geo: GeoPos, mc: f64, keys: Vec<char>, aya_offset: f64) -> HouseSetData {
+  let target_armc = ecliptic_to_equatorial_basic(ref_jd, mc, 0f64).lng;
+  let armc = get_house_data(ref_jd, geo.lat, geo.lng, 'W', false).points.armc;
+  let houses_jd = ref_jd + normalize_180(target_armc - armc) / EARTH_ROTATION_PER_DAY;
+  get_house_systems(houses_jd, geo, keys, aya_offset)
+}
+
 pub fn get_all_house_systems(jd: f64, geo: GeoPos, aya_offset: f64) -> HouseSetData {
   get_house_systems(jd, geo, vec!['a'], aya_offset)
 }
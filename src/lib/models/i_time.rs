@@ -7,9 +7,9 @@ pub struct ITime {
   year: i32,
   #[serde(rename="dayNum")]
   day_num: u32,
-  progress: f64,
+  pub progress: f64,
   #[serde(rename="dayLength")]
-  day_length: f64,
+  pub day_length: f64,
   #[serde(rename="isDayTime")]
   is_day_time: bool,
   #[serde(rename="dayBefore")]
@@ -19,7 +19,7 @@ pub struct ITime {
   vighati: u8,
   lipta: f64,
   #[serde(rename="weekDayNum")]
-  week_day_num: u8,
+  pub week_day_num: u8,
 }
 
 impl ITime {
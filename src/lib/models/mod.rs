@@ -3,4 +3,6 @@ pub mod graha_pos;
 pub mod general;
 pub mod houses;
 pub mod date_info;
-pub mod i_time;
\ No newline at end of file
+pub mod i_time;
+pub mod body_meta;
+pub mod varga;
\ No newline at end of file
@@ -0,0 +1,47 @@
+use serde::{Serialize, Deserialize};
+use super::super::{math_funcs::{calc_varga_sign_progress, subtract_360}, settings::varga_values::{VargaItem}};
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct VargaValue {
+  pub num: u16,
+  pub key: String,
+  pub sign: u8,
+  pub lng: f64,
+}
+
+impl VargaValue {
+  pub fn new(lng: f64, item: &VargaItem) -> VargaValue {
+    let (sign_index, progress) = calc_varga_sign_progress(lng, item.num);
+    VargaValue {
+      num: item.num,
+      key: item.key.to_string(),
+      sign: sign_index as u8 + 1,
+      lng: sign_index as f64 * 30f64 + progress * 30f64,
+    }
+  }
+}
+
+/*
+* Varga signs of one body or point in each requested divisional chart
+*/
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct VargaSet {
+  pub key: String,
+  pub values: Vec<VargaValue>,
+}
+
+impl VargaSet {
+  pub fn new(key: &str, sid_lng: f64, items: &Vec<VargaItem>) -> VargaSet {
+    VargaSet {
+      key: key.to_string(),
+      values: items.iter().map(|item| VargaValue::new(sid_lng, item)).collect(),
+    }
+  }
+}
+
+/*
+* Varga sets from longitudes that may still need to be adjusted by the ayanamsha, as divisional charts are always sidereal
+*/
+pub fn to_varga_sets(items: Vec<(String, f64)>, aya_offset: f64, varga_items: &Vec<VargaItem>) -> Vec<VargaSet> {
+  items.into_iter().map(|(key, lng)| VargaSet::new(key.as_str(), subtract_360(lng, aya_offset), varga_items)).collect()
+}
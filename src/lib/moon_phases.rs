@@ -0,0 +1,131 @@
+use serde::{Serialize, Deserialize};
+use super::{core::{calc_body_jd_geo}, transitions::{get_pheno_result}, julian_date::{julian_day_to_iso_datetime}, math_funcs::{subtract_360}, aspect_transits::{normalize_180}};
+
+/*
+* Mean synodic month in days
+*/
+pub const SYNODIC_MONTH: f64 = 29.530588853;
+
+pub const MOON_PHASE_KEYS: [&str; 4] = ["new", "first_quarter", "full", "last_quarter"];
+
+pub const MOON_PHASE_NAMES_8: [&str; 8] = ["new", "waxing_crescent", "first_quarter", "waxing_gibbous", "full", "waning_gibbous", "last_quarter", "waning_crescent"];
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct MoonPhaseEvent {
+  pub key: String,
+  pub num: u8,
+  pub angle: f64,
+  pub jd: f64,
+  #[serde(skip_serializing_if = "String::is_empty")]
+  pub dt: String,
+  #[serde(rename="moonLng")]
+  pub moon_lng: f64,
+  pub illumination: f64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct MoonPhaseState {
+  pub phase: String,
+  pub elongation: f64,
+  #[serde(rename="phaseAngle")]
+  pub phase_angle: f64,
+  pub illumination: f64,
+  pub waxing: bool,
+  pub age: f64,
+}
+
+/*
+* Elongation of the Moon from the Sun from 0º to 360º with its rate of change in degrees per day
+*/
+pub fn calc_moon_elongation(jd: f64) -> (f64, f64) {
+  let sun = calc_body_jd_geo(jd, "su", 0f64);
+  let moon = calc_body_jd_geo(jd, "mo", 0f64);
+  (subtract_360(moon.lng, sun.lng), moon.lng_speed - sun.lng_speed)
+}
+
+/*
+* Solve the time when the elongation reaches the target angle via Newton's method
+*/
+pub fn calc_elongation_crossing(jd: f64, target: f64) -> f64 {
+  let mut ref_jd = jd;
+  for _ in 0..20 {
+    let (angle, speed) = calc_moon_elongation(ref_jd);
+    let diff = normalize_180(target - angle);
+    if diff.abs() < 0.000001f64 || speed <= 0f64 {
+      break;
+    }
+    ref_jd += diff / speed;
+  }
+  ref_jd
+}
+
+/*
+* Name of one of 8 phases centred on the new moon, quarters and full moon
+*/
+pub fn match_moon_phase_name(elongation: f64) -> &'static str {
+  let index = ((subtract_360(elongation, 0f64) + 22.5f64) / 45f64).floor() as usize % 8;
+  MOON_PHASE_NAMES_8[index]
+}
+
+pub fn calc_moon_phase_state(jd: f64) -> MoonPhaseState {
+  let (elongation, speed) = calc_moon_elongation(jd);
+  let pheno = get_pheno_result(jd, "mo", 0i32);
+  let age = if speed > 0f64 { jd - calc_elongation_crossing(jd - elongation / speed, 0f64) } else { 0f64 };
+  MoonPhaseState {
+    phase: match_moon_phase_name(elongation).to_string(),
+    elongation,
+    phase_angle: pheno.phase_angle,
+    illumination: pheno.phase_illuminated,
+    waxing: elongation < 180f64,
+    age,
+  }
+}
+
+/*
+* Exact new moon, first quarter, full moon and last quarter times within the time span
+*/
+pub fn calc_moon_phases_range(start_jd: f64, end_jd: f64, iso_mode: bool) -> Vec<MoonPhaseEvent> {
+  let mut items: Vec<MoonPhaseEvent> = Vec::new();
+  let (start_angle, _) = calc_moon_elongation(start_jd);
+  let mut index = (start_angle / 90f64).floor() as usize + 1;
+  let mut ref_jd = start_jd;
+  loop {
+    let num = index % 4;
+    let angle = num as f64 * 90f64;
+    let (curr_angle, _) = calc_moon_elongation(ref_jd);
+    let estimate = ref_jd + subtract_360(angle, curr_angle) / 360f64 * SYNODIC_MONTH;
+    let jd = calc_elongation_crossing(estimate, angle);
+    if jd > end_jd {
+      break;
+    }
+    if jd >= start_jd {
+      let dt = if iso_mode { julian_day_to_iso_datetime(jd) } else { "".to_string() };
+      items.push(MoonPhaseEvent {
+        key: MOON_PHASE_KEYS[num].to_string(),
+        num: num as u8,
+        angle,
+        jd,
+        dt,
+        moon_lng: calc_body_jd_geo(jd, "mo", 0f64).lng,
+        illumination: get_pheno_result(jd, "mo", 0i32).phase_illuminated,
+      });
+    }
+    ref_jd = jd + 1f64;
+    index += 1;
+  }
+  items
+}
+
+#[cfg(test)]
+mod tests {
+  use super::match_moon_phase_name;
+
+  #[test]
+  fn matches_moon_phase_names() {
+    assert_eq!(match_moon_phase_name(5f64), "new");
+    assert_eq!(match_moon_phase_name(355f64), "new");
+    assert_eq!(match_moon_phase_name(60f64), "waxing_crescent");
+    assert_eq!(match_moon_phase_name(180f64), "full");
+    assert_eq!(match_moon_phase_name(300f64), "waning_crescent");
+  }
+}
@@ -0,0 +1,193 @@
+use serde::{Serialize, Deserialize};
+use super::{core::{calc_body_jd_geo_sidereal}, transitions::{to_indian_time_with_transitions}, julian_date::{julian_day_to_iso_datetime}, math_funcs::{subtract_360}, aspect_transits::{normalize_180}};
+use super::models::{geo_pos::GeoPos, i_time::ITime};
+use super::settings::{ayanamshas::{match_ayanamsha_key}, panchanga_values::*, nakshatra_values::{NAKSHATRA_NAMES_27, NAKSHATRA_LORDS}};
+use super::super::extensions::swe::{Atmosphere, SweError};
+
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq)]
+pub enum PanchangaLimb {
+  Tithi,
+  Nakshatra,
+  Yoga,
+  Karana,
+}
+
+impl PanchangaLimb {
+
+  /*
+  * Span of each division of the limb angle in degrees
+  */
+  pub fn span(&self) -> f64 {
+    match self {
+      PanchangaLimb::Tithi => 12f64,
+      PanchangaLimb::Karana => 6f64,
+      _ => 360f64 / 27f64,
+    }
+  }
+
+  pub fn num_parts(&self) -> usize {
+    match self {
+      PanchangaLimb::Tithi => 30,
+      PanchangaLimb::Karana => 60,
+      _ => 27,
+    }
+  }
+
+  /*
+  * Name and ruling planet, if any, of a division from its zero-based index
+  */
+  pub fn match_name(&self, index: usize) -> (&'static str, &'static str) {
+    match self {
+      PanchangaLimb::Tithi => match index {
+        14 => ("purnima", ""),
+        29 => ("amavasya", ""),
+        _ => (TITHI_NAMES[index % 15], ""),
+      },
+      PanchangaLimb::Nakshatra => (NAKSHATRA_NAMES_27[index % 27], NAKSHATRA_LORDS[index % 9]),
+      PanchangaLimb::Yoga => (YOGA_NAMES[index % 27], ""),
+      PanchangaLimb::Karana => match index {
+        0 => (FIXED_KARANA_NAMES[3], ""),
+        57..=59 => (FIXED_KARANA_NAMES[index - 57], ""),
+        _ => (MOVABLE_KARANA_NAMES[(index - 1) % 7], ""),
+      },
+    }
+  }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PanchangaItem {
+  pub num: u8,
+  pub name: String,
+  #[serde(skip_serializing_if = "String::is_empty")]
+  pub lord: String,
+  pub start: f64,
+  pub end: f64,
+  #[serde(rename="startDt",skip_serializing_if = "String::is_empty")]
+  pub start_dt: String,
+  #[serde(rename="endDt",skip_serializing_if = "String::is_empty")]
+  pub end_dt: String,
+}
+
+impl PanchangaItem {
+  pub fn new(num: u8, name: &str, lord: &str, start: f64, end: f64, iso_mode: bool) -> PanchangaItem {
+    let start_dt = if iso_mode { julian_day_to_iso_datetime(start) } else { "".to_string() };
+    let end_dt = if iso_mode { julian_day_to_iso_datetime(end) } else { "".to_string() };
+    PanchangaItem { num, name: name.to_string(), lord: lord.to_string(), start, end, start_dt, end_dt }
+  }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PanchangaSet {
+  pub ayanamsha: String,
+  #[serde(rename="dayStart")]
+  pub day_start: f64,
+  #[serde(rename="dayEnd")]
+  pub day_end: f64,
+  #[serde(rename="indianTime")]
+  pub indian_time: ITime,
+  pub vara: PanchangaItem,
+  pub tithi: Vec<PanchangaItem>,
+  pub nakshatra: Vec<PanchangaItem>,
+  pub yoga: Vec<PanchangaItem>,
+  pub karana: Vec<PanchangaItem>,
+}
+
+/*
+* Limb angle with its rate of change in degrees per day from the sidereal Sun and Moon.
+* Tithis and karanas depend only on the elongation of the Moon and are thus not affected by the ayanamsha
+*/
+pub fn calc_limb_angle(jd: f64, limb: PanchangaLimb, aya_key: &str) -> (f64, f64) {
+  let moon = calc_body_jd_geo_sidereal(jd, "mo", aya_key);
+  match limb {
+    PanchangaLimb::Nakshatra => (moon.lng, moon.lng_speed),
+    _ => {
+      let sun = calc_body_jd_geo_sidereal(jd, "su", aya_key);
+      match limb {
+        PanchangaLimb::Yoga => ((sun.lng + moon.lng) % 360f64, sun.lng_speed + moon.lng_speed),
+        _ => (subtract_360(moon.lng, sun.lng), moon.lng_speed - sun.lng_speed),
+      }
+    }
+  }
+}
+
+/*
+* Solve the time when the limb angle reaches the target longitude via Newton's method,
+* as the limb angle always increases with the Moon's motion
+*/
+pub fn calc_limb_crossing(jd: f64, limb: PanchangaLimb, aya_key: &str, target: f64) -> f64 {
+  let mut ref_jd = jd;
+  for _ in 0..20 {
+    let (angle, speed) = calc_limb_angle(ref_jd, limb, aya_key);
+    let diff = normalize_180(target - angle);
+    if diff.abs() < 0.000001f64 || speed <= 0f64 {
+      break;
+    }
+    ref_jd += diff / speed;
+  }
+  ref_jd
+}
+
+/*
+* All divisions of one limb prevailing during the day from the one current at sunrise,
+* so skipped (kshaya) tithis or nakshatras are included
+*/
+pub fn calc_limb_items(day_start: f64, day_end: f64, limb: PanchangaLimb, aya_key: &str, iso_mode: bool) -> Vec<PanchangaItem> {
+  let span = limb.span();
+  let num_parts = limb.num_parts();
+  let (angle, _) = calc_limb_angle(day_start, limb, aya_key);
+  let mut index = (angle / span).floor() as usize % num_parts;
+  let mut start = calc_limb_crossing(day_start, limb, aya_key, index as f64 * span);
+  let mut items: Vec<PanchangaItem> = Vec::new();
+  while items.len() < 6 {
+    let target = ((index + 1) as f64 * span) % 360f64;
+    let end = calc_limb_crossing(start + 0.01f64, limb, aya_key, target);
+    let (name, lord) = limb.match_name(index);
+    items.push(PanchangaItem::new(index as u8 + 1, name, lord, start, end, iso_mode));
+    if end >= day_end {
+      break;
+    }
+    start = end;
+    index = (index + 1) % num_parts;
+  }
+  items
+}
+
+/*
+* Panchanga for the sunrise-to-sunrise day at a location. The vara ends at the next sunrise
+*/
+pub fn calc_panchanga(jd: f64, geo: GeoPos, atmosphere: Atmosphere, aya_key: &str, offset_secs: Option<i16>, iso_mode: bool) -> Result<PanchangaSet, SweError> {
+  let (indian_time, _, _, _, _) = to_indian_time_with_transitions(jd, geo, atmosphere, offset_secs, iso_mode)?;
+  let day_start = jd - indian_time.progress * indian_time.day_length;
+  let day_end = day_start + indian_time.day_length;
+  let vara_index = (indian_time.week_day_num as usize + 6) % 7;
+  let (vara_name, vara_lord) = VARA_NAMES[vara_index];
+  Ok(PanchangaSet {
+    ayanamsha: match_ayanamsha_key(aya_key),
+    day_start,
+    day_end,
+    indian_time,
+    vara: PanchangaItem::new(vara_index as u8 + 1, vara_name, vara_lord, day_start, day_end, iso_mode),
+    tithi: calc_limb_items(day_start, day_end, PanchangaLimb::Tithi, aya_key, iso_mode),
+    nakshatra: calc_limb_items(day_start, day_end, PanchangaLimb::Nakshatra, aya_key, iso_mode),
+    yoga: calc_limb_items(day_start, day_end, PanchangaLimb::Yoga, aya_key, iso_mode),
+    karana: calc_limb_items(day_start, day_end, PanchangaLimb::Karana, aya_key, iso_mode),
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::PanchangaLimb;
+
+  #[test]
+  fn matches_tithi_and_karana_names() {
+    assert_eq!(PanchangaLimb::Tithi.match_name(0).0, "pratipada");
+    assert_eq!(PanchangaLimb::Tithi.match_name(14).0, "purnima");
+    assert_eq!(PanchangaLimb::Tithi.match_name(15).0, "pratipada");
+    assert_eq!(PanchangaLimb::Tithi.match_name(29).0, "amavasya");
+    assert_eq!(PanchangaLimb::Karana.match_name(0).0, "kimstughna");
+    assert_eq!(PanchangaLimb::Karana.match_name(1).0, "bava");
+    assert_eq!(PanchangaLimb::Karana.match_name(56).0, "vishti");
+    assert_eq!(PanchangaLimb::Karana.match_name(57).0, "shakuni");
+    assert_eq!(PanchangaLimb::Karana.match_name(59).0, "naga");
+  }
+}
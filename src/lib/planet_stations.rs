@@ -1,5 +1,5 @@
 use ::serde::{Serialize, Deserialize};
-use super::{julian_date::{julian_day_to_iso_datetime}, data::body_speeds::*};
+use super::{julian_date::{julian_day_to_iso_datetime}, data::body_speeds::*, core::{calc_body_jd}};
 
 pub enum PlanetStation {
 	RetroStart = 0,
@@ -132,7 +132,12 @@ pub fn match_planet_stations_range(key: &str, ref_jd: f64, end_jd: f64) -> Vec<B
 }
 
 pub fn match_nextprev_planet_stations(key: &str, ref_jd: f64) -> Vec<BodySpeed> {
-	match_planet_stations_range(key, ref_jd, 0f64)
+	let span = station_search_span(key);
+	if is_in_station_table(key, ref_jd - span, ref_jd + span) {
+		match_planet_stations_range(key, ref_jd, 0f64)
+	} else {
+		calc_planet_stations(key, ref_jd - span, ref_jd + span)
+	}
 }
 
 pub fn match_all_nextprev_planet_stations(ref_jd: f64, bodies: Vec<&str>, iso_mode: bool) -> Vec<BodySpeedSet> {
@@ -144,11 +149,203 @@ pub fn match_all_nextprev_planet_stations(ref_jd: f64, bodies: Vec<&str>, iso_mo
 	items
 }
 
+/*
+* Use the station tables as a fast path if the whole range is covered, otherwise calculate stations on the fly
+*/
+pub fn match_or_calc_planet_stations_range(key: &str, ref_jd: f64, end_jd: f64) -> Vec<BodySpeed> {
+	if is_in_station_table(key, ref_jd, end_jd) {
+		match_planet_stations_range(key, ref_jd, end_jd)
+	} else {
+		calc_planet_stations(key, ref_jd, end_jd)
+	}
+}
+
 pub fn match_all_planet_stations_range(ref_jd: f64, end_jd: f64, bodies: Vec<&str>, iso_mode: bool) -> Vec<BodySpeedSet> {
 	let mut items: Vec<BodySpeedSet> = vec![];
 	for key in bodies {
-		let rows = match_planet_stations_range(key, ref_jd, end_jd);
+		let rows = match_or_calc_planet_stations_range(key, ref_jd, end_jd);
 		items.push(BodySpeedSet::new(key, rows, iso_mode));
 	}
 	items
+}
+
+fn match_station_table(key: &str) -> &'static [(f64, f64, f64, u8)] {
+	match key {
+		"me" => PLANETARY_STATIONS_ME,
+		"ve" => PLANETARY_STATIONS_VE,
+		"ma" => PLANETARY_STATIONS_MA,
+		"ju" => PLANETARY_STATIONS_JU,
+		"sa" => PLANETARY_STATIONS_SA,
+		"ur" => PLANETARY_STATIONS_UR,
+		"ne" => PLANETARY_STATIONS_NE,
+		"pl" => PLANETARY_STATIONS_PL,
+		_ => PLANETARY_STATIONS_EA,
+	}
+}
+
+/*
+* First and last julian days covered by the station table of a body, if any. Tables are in descending order
+*/
+pub fn station_table_range(key: &str) -> Option<(f64, f64)> {
+	let rows = match_station_table(key);
+	match (rows.last(), rows.first()) {
+		(Some(first), Some(last)) => Some((first.0, last.0)),
+		_ => None,
+	}
+}
+
+pub fn is_in_station_table(key: &str, start_jd: f64, end_jd: f64) -> bool {
+	let target_jd = if end_jd < start_jd { start_jd } else { end_jd };
+	match station_table_range(key) {
+		Some((first_jd, last_jd)) => start_jd >= first_jd && target_jd <= last_jd,
+		_ => false,
+	}
+}
+
+/*
+* Sample interval in days for the station solver, short enough to never skip a retrograde period
+*/
+pub fn station_sample_step(key: &str) -> f64 {
+	match key {
+		"me" | "ve" => 1f64,
+		"ma" => 2f64,
+		"ju" | "sa" | "ur" | "ne" | "pl" => 4f64,
+		_ => 2f64,
+	}
+}
+
+/*
+* Approximate synodic period in days to find previous and next stations around a reference time
+*/
+pub fn station_search_span(key: &str) -> f64 {
+	match key {
+		"me" => 120f64,
+		"ve" => 590f64,
+		"ma" => 790f64,
+		"ju" => 400f64,
+		_ => 380f64,
+	}
+}
+
+fn calc_body_lng_speed(jd: f64, key: &str) -> (f64, f64) {
+	let pos = calc_body_jd(jd, key, false, false, 0f64);
+	(pos.lng, pos.lng_speed)
+}
+
+/*
+* Retrograde and direct stations and speed peaks of any body over a time span calculated from geocentric speeds.
+* The Sun, Moon and Earth have no stations
+*/
+pub fn calc_planet_stations(key: &str, start_jd: f64, end_jd: f64) -> Vec<BodySpeed> {
+	match key {
+		"su" | "mo" | "ea" => vec![],
+		_ => calc_stations_with(key, start_jd, end_jd, station_sample_step(key), &|jd| calc_body_lng_speed(jd, key)),
+	}
+}
+
+/*
+* Bisect between two sample times where the speed changes sign
+*/
+fn refine_speed_zero(calc: &dyn Fn(f64) -> (f64, f64), start_jd: f64, end_jd: f64) -> f64 {
+	let mut jd1 = start_jd;
+	let mut jd2 = end_jd;
+	let start_sign = calc(start_jd).1.signum();
+	for _ in 0..40 {
+		let mid_jd = (jd1 + jd2) / 2f64;
+		if calc(mid_jd).1.signum() == start_sign {
+			jd1 = mid_jd;
+		} else {
+			jd2 = mid_jd;
+		}
+	}
+	(jd1 + jd2) / 2f64
+}
+
+/*
+* Golden section search for the time of minimum or maximum speed within the interval
+*/
+fn refine_speed_extreme(calc: &dyn Fn(f64) -> (f64, f64), start_jd: f64, end_jd: f64, is_max: bool) -> f64 {
+	let ratio = (5f64.sqrt() - 1f64) / 2f64;
+	let mut jd1 = start_jd;
+	let mut jd2 = end_jd;
+	let sign = if is_max { -1f64 } else { 1f64 };
+	for _ in 0..40 {
+		let c = jd2 - ratio * (jd2 - jd1);
+		let d = jd1 + ratio * (jd2 - jd1);
+		if sign * calc(c).1 < sign * calc(d).1 {
+			jd2 = d;
+		} else {
+			jd1 = c;
+		}
+	}
+	(jd1 + jd2) / 2f64
+}
+
+/*
+* Stations from any longitude and speed function. Speed zero crossings mark the start and end of retrograde periods.
+* Only the fastest retrograde or direct speed between two consecutive sign changes is a peak, so minor wobbles are ignored.
+*/
+pub fn calc_stations_with(key: &str, start_jd: f64, end_jd: f64, step: f64, calc: &dyn Fn(f64) -> (f64, f64)) -> Vec<BodySpeed> {
+	let mut samples: Vec<(f64, f64)> = Vec::new();
+	let mut ref_jd = start_jd;
+	while ref_jd < end_jd {
+		samples.push((ref_jd, calc(ref_jd).1));
+		ref_jd += step;
+	}
+	samples.push((end_jd, calc(end_jd).1));
+	let num_samples = samples.len();
+	// indices of the last sample before each speed sign change
+	let mut switch_indices: Vec<usize> = Vec::new();
+	for i in 1..num_samples {
+		if samples[i - 1].1.signum() != samples[i].1.signum() {
+			switch_indices.push(i - 1);
+		}
+	}
+	let mut items: Vec<BodySpeed> = Vec::new();
+	let num_switches = switch_indices.len();
+	let mut segment_start = 0usize;
+	for n in 0..=num_switches {
+		let segment_end = if n < num_switches { switch_indices[n] } else { num_samples - 1 };
+		let is_retro = samples[segment_start].1 < 0f64;
+		let mut ext_index = segment_start;
+		for i in segment_start..=segment_end {
+			let is_more = if is_retro { samples[i].1 < samples[ext_index].1 } else { samples[i].1 > samples[ext_index].1 };
+			if is_more {
+				ext_index = i;
+			}
+		}
+		// extremes at the edge of a segment are not peaks
+		if ext_index > segment_start && ext_index < segment_end {
+			let jd = refine_speed_extreme(calc, samples[ext_index - 1].0, samples[ext_index + 1].0, !is_retro);
+			let (lng, speed) = calc(jd);
+			let station = if is_retro { PlanetStation::RetroPeak } else { PlanetStation::Peak };
+			items.push(BodySpeed::new(key, jd, lng, speed, station as u8));
+		}
+		if n < num_switches {
+			let jd = refine_speed_zero(calc, samples[segment_end].0, samples[segment_end + 1].0);
+			let (lng, speed) = calc(jd);
+			let station = if is_retro { PlanetStation::RetroEnd } else { PlanetStation::RetroStart };
+			items.push(BodySpeed::new(key, jd, lng, speed, station as u8));
+			segment_start = segment_end + 1;
+		}
+	}
+	items
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn calculates_stations_from_speed_curve() {
+		// retrograde from day 33.3 to 66.7 of each 100-day cycle with peaks at day 50 and 100
+		let calc = |jd: f64| (jd, 1f64 + 2f64 * (jd * std::f64::consts::PI / 50f64).cos());
+		let items = calc_stations_with("ma", 10f64, 120f64, 2f64, &calc);
+		let stations: Vec<&str> = items.iter().map(|s| s.station.as_str()).collect();
+		assert_eq!(stations, vec!["retro-start", "retro-peak", "retro-end", "peak"]);
+		assert!((items[0].jd - 100f64 / 3f64).abs() < 0.0001f64);
+		assert!((items[1].jd - 50f64).abs() < 0.0001f64);
+		assert!((items[2].jd - 200f64 / 3f64).abs() < 0.0001f64);
+		assert!((items[3].jd - 100f64).abs() < 0.0001f64);
+	}
 }
\ No newline at end of file
@@ -0,0 +1,134 @@
+use serde::{Serialize, Deserialize};
+use super::{core::{calc_body_jd_geo}, julian_date::{julian_day_to_iso_datetime}, math_funcs::{to_progression_jd, get_year_length}, aspect_transits::{normalize_180}};
+use super::models::{geo_pos::GeoPos, houses::{HouseSetData, get_house_data, get_house_systems_for_mc}};
+
+/*
+* Mean daily motion of the Sun in degrees, applied as the yearly arc in the Naibod method
+*/
+pub const NAIBOD_ARC: f64 = 0.98564733;
+
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq)]
+pub enum ProgressionMethod {
+  SolarArc,
+  Naibod,
+}
+
+impl ProgressionMethod {
+  pub fn as_key(&self) -> &str {
+    match self {
+      ProgressionMethod::SolarArc => "solar_arc",
+      ProgressionMethod::Naibod => "naibod",
+    }
+  }
+}
+
+/*
+* Match the method for progressing the MC from a query string value, solar arc by default
+*/
+pub fn match_progression_method(ref_str: &str) -> ProgressionMethod {
+  match ref_str.to_lowercase().as_str() {
+    "naibod" | "nb" | "n" => ProgressionMethod::Naibod,
+    _ => ProgressionMethod::SolarArc,
+  }
+}
+
+/*
+* Normalise year type keys to those recognised by get_year_length
+*/
+pub fn match_year_type(ref_str: &str) -> &'static str {
+  match ref_str.to_lowercase().as_str() {
+    "sidereal" | "sid" => "sidereal",
+    "anomalistic" | "anom" => "anomalistic",
+    _ => "tropical",
+  }
+}
+
+/*
+* Arc in degrees to be added to the natal MC. The solar arc is the distance travelled by the progressed Sun,
+* while the Naibod arc assumes the mean motion of the Sun per progressed year
+*/
+pub fn calc_mc_arc(method: ProgressionMethod, natal_sun_lng: f64, progressed_sun_lng: f64, years: f64) -> f64 {
+  match method {
+    ProgressionMethod::SolarArc => normalize_180(progressed_sun_lng - natal_sun_lng).rem_euclid(360f64),
+    ProgressionMethod::Naibod => (years * NAIBOD_ARC).rem_euclid(360f64),
+  }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ProgressedChart {
+  pub jd: f64,
+  #[serde(rename="targetJd")]
+  pub target_jd: f64,
+  pub pd: f64,
+  #[serde(skip_serializing_if = "String::is_empty")]
+  pub dt: String,
+  pub years: f64,
+  #[serde(rename="yearType")]
+  pub year_type: String,
+  #[serde(rename="yearLength")]
+  pub year_length: f64,
+  pub method: String,
+  pub arc: f64,
+  #[serde(rename="natalMc")]
+  pub natal_mc: f64,
+  pub mc: f64,
+  pub house: HouseSetData,
+}
+
+/*
+* Progressed MC from the natal MC and the arc of the referenced method, with the arc itself
+*/
+pub fn calc_progressed_mc(natal_jd: f64, pd: f64, geo: GeoPos, method: ProgressionMethod, years: f64) -> (f64, f64, f64) {
+  let natal_mc = get_house_data(natal_jd, geo.lat, geo.lng, 'W', false).points.mc;
+  let arc = calc_mc_arc(method, calc_body_jd_geo(natal_jd, "su", 0f64).lng, calc_body_jd_geo(pd, "su", 0f64).lng, years);
+  (natal_mc, arc, (natal_mc + arc).rem_euclid(360f64))
+}
+
+/*
+* Secondary progression of a natal chart to the target time, where one day after birth corresponds to one year of life
+*/
+pub fn calc_progressed_chart(natal_jd: f64, target_jd: f64, geo: GeoPos, method: ProgressionMethod, year_type: &str, h_systems: Vec<char>, aya_offset: f64, iso_mode: bool) -> ProgressedChart {
+  let year_key = match_year_type(year_type);
+  let year_length = get_year_length(year_key);
+  let pd = to_progression_jd(natal_jd, target_jd, year_key);
+  let years = (target_jd - natal_jd) / year_length;
+  let (natal_mc, arc, mc) = calc_progressed_mc(natal_jd, pd, geo, method, years);
+  let house = get_house_systems_for_mc(pd, geo, mc, h_systems, aya_offset);
+  let dt = if iso_mode { julian_day_to_iso_datetime(pd) } else { "".to_string() };
+  ProgressedChart {
+    jd: natal_jd,
+    target_jd,
+    pd,
+    dt,
+    years,
+    year_type: year_key.to_string(),
+    year_length,
+    method: method.as_key().to_string(),
+    arc,
+    natal_mc,
+    mc,
+    house,
+  }
+}
+
+/*
+* Progressed ascendant from the MC progressed by the referenced method for progress synastry sets
+*/
+pub fn calc_progressed_ascendant(natal_jd: f64, pd: f64, geo: GeoPos, method: ProgressionMethod, years: f64) -> f64 {
+  let (_, _, mc) = calc_progressed_mc(natal_jd, pd, geo, method, years);
+  get_house_systems_for_mc(pd, geo, mc, vec!['W'], 0f64).points.ascendant
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn calculates_mc_arcs() {
+    assert!((calc_mc_arc(ProgressionMethod::Naibod, 0f64, 0f64, 10f64) - 9.8564733f64).abs() < 0.0000001f64);
+    assert!((calc_mc_arc(ProgressionMethod::SolarArc, 355f64, 25f64, 0f64) - 30f64).abs() < 0.0000001f64);
+    assert!((calc_mc_arc(ProgressionMethod::SolarArc, 25f64, 355f64, 0f64) - 330f64).abs() < 0.0000001f64);
+    assert_eq!(match_progression_method("naibod"), ProgressionMethod::Naibod);
+    assert_eq!(match_year_type("Sidereal"), "sidereal");
+  }
+}
@@ -0,0 +1,70 @@
+use super::{core::{calc_body_jd_geo, get_ayanamsha_value}, aspect_transits::{sample_longitudes_with, match_crossings_with}, julian_date::{datetime_to_julian_day}, settings::graha_values::{match_body_num}};
+
+/*
+* Match the returning body from the return type, solar, lunar or any body key. None if the key is unknown
+*/
+pub fn match_return_body(ref_str: &str) -> Option<String> {
+  match ref_str.trim().to_lowercase().as_str() {
+    "" | "solar" | "sun" => Some("su".to_string()),
+    "lunar" | "moon" => Some("mo".to_string()),
+    key => match_body_num(key).map(|_| key.to_string()),
+  }
+}
+
+/*
+* Default search span in days when no year or month is specified
+*/
+pub fn return_search_days(key: &str) -> u16 {
+  match key {
+    "mo" => 28,
+    _ => 366,
+  }
+}
+
+/*
+* Start and end of a calendar year or month, where the month is one-based
+*/
+pub fn calendar_period_jds(year: i32, month: u8) -> (f64, f64) {
+  match month {
+    1..=12 => {
+      let (next_year, next_month) = if month == 12 { (year + 1, 1) } else { (year, month + 1) };
+      (datetime_to_julian_day(format!("{:04}-{:02}-01T00:00:00", year, month).as_str()), datetime_to_julian_day(format!("{:04}-{:02}-01T00:00:00", next_year, next_month).as_str()))
+    },
+    _ => (datetime_to_julian_day(format!("{:04}-01-01T00:00:00", year).as_str()), datetime_to_julian_day(format!("{:04}-01-01T00:00:00", year + 1).as_str())),
+  }
+}
+
+/*
+* Tropical longitude or sidereal with the ayanamsha offset at the referenced time
+*/
+pub fn calc_return_lng(jd: f64, key: &str, sidereal: bool, aya_key: &str) -> f64 {
+  let aya_offset = if sidereal { get_ayanamsha_value(jd, aya_key) } else { 0f64 };
+  calc_body_jd_geo(jd, key, aya_offset).lng
+}
+
+/*
+* All times within the span when the body returns to its natal longitude with that natal longitude.
+* Planets may return up to three times around a station
+*/
+pub fn calc_returns(natal_jd: f64, key: &str, sidereal: bool, aya_key: &str, start_jd: f64, end_jd: f64) -> (f64, Vec<f64>) {
+  let calc_lng = |jd: f64| calc_return_lng(jd, key, sidereal, aya_key);
+  let natal_lng = calc_lng(natal_jd);
+  let samples = sample_longitudes_with(key, start_jd, end_jd, &calc_lng);
+  (natal_lng, match_crossings_with(&calc_lng, natal_lng, &samples))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn matches_calendar_periods() {
+    let (start, end) = calendar_period_jds(2024, 0);
+    assert!((end - start - 366f64).abs() < 0.000001f64);
+    let (start, end) = calendar_period_jds(2023, 12);
+    assert!((end - start - 31f64).abs() < 0.000001f64);
+    assert_eq!(match_return_body("lunar"), Some("mo".to_string()));
+    assert_eq!(match_return_body("JU"), Some("ju".to_string()));
+    assert_eq!(match_return_body("xyz"), None);
+  }
+}
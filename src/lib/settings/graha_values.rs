@@ -25,6 +25,18 @@ impl FromKey<Bodies> for Bodies {
       "ce" => Bodies::Ceres,
       "ch" => Bodies::Chiron,
       "sn" => Bodies::SouthNode,
+      "lm" => Bodies::MeanApog,
+      "lt" => Bodies::OscuApog,
+      "ph" => Bodies::Pholus,
+      "pa" => Bodies::Pallas,
+      "vs" => Bodies::Vesta,
+      "cu" => Bodies::Cupido,
+      "ha" => Bodies::Hades,
+      "ze" => Bodies::Zeus,
+      "ap" => Bodies::Apollon,
+      "ad" => Bodies::Admetos,
+      "vu" => Bodies::Vulkanus,
+      "po" => Bodies::Poseidon,
       _ => Bodies::Earth,
     }
   }
@@ -53,11 +65,124 @@ impl ToKey<Bodies> for Bodies {
       Bodies::Ceres => "ce",
       Bodies::Chiron => "ch",
       Bodies::SouthNode => "sn",
+      Bodies::MeanApog => "lm",
+      Bodies::OscuApog => "lt",
+      Bodies::Pholus => "ph",
+      Bodies::Pallas => "pa",
+      Bodies::Vesta => "vs",
+      Bodies::Cupido => "cu",
+      Bodies::Hades => "ha",
+      Bodies::Zeus => "ze",
+      Bodies::Apollon => "ap",
+      Bodies::Admetos => "ad",
+      Bodies::Vulkanus => "vu",
+      Bodies::Poseidon => "po",
       _ => "ea",
     }
   }
 }
 
+/*
+* Offset of MPC asteroid numbers in the Swiss Ephemeris
+*/
+pub const AST_OFFSET: i32 = 10000;
+
+/*
+* Valid body keys with their names and Swiss Ephemeris numbers. Eris and Sedna are numbered asteroids
+*/
+pub const BODY_KEYS: [(&'static str, &'static str, i32); 34] = [
+  ("su", "Sun", 0),
+  ("mo", "Moon", 1),
+  ("me", "Mercury", 2),
+  ("ve", "Venus", 3),
+  ("ma", "Mars", 4),
+  ("ju", "Jupiter", 5),
+  ("sa", "Saturn", 6),
+  ("ur", "Uranus", 7),
+  ("ne", "Neptune", 8),
+  ("pl", "Pluto", 9),
+  ("mn", "Mean Node", 10),
+  ("ra", "Rahu (True Node)", 11),
+  ("ke", "Ketu", 11),
+  ("sn", "South Node", 24),
+  ("lm", "Mean Black Moon Lilith", 12),
+  ("lt", "True Black Moon Lilith (osculating apogee)", 13),
+  ("ea", "Earth", 14),
+  ("ch", "Chiron", 15),
+  ("ph", "Pholus", 16),
+  ("ce", "Ceres", 17),
+  ("pa", "Pallas", 18),
+  ("jn", "Juno", 19),
+  ("vs", "Vesta", 20),
+  ("er", "Eris", AST_OFFSET + 136199),
+  ("se", "Sedna", AST_OFFSET + 90377),
+  ("cu", "Cupido", 40),
+  ("ha", "Hades", 41),
+  ("ze", "Zeus", 42),
+  ("kr", "Kronos", 43),
+  ("ap", "Apollon", 44),
+  ("ad", "Admetos", 45),
+  ("vu", "Vulkanus", 46),
+  ("po", "Poseidon", 47),
+  ("is", "Isis", 48),
+];
+
+/*
+* Swiss Ephemeris body number from a body key or an MPC-numbered asteroid as ast:number, e.g. ast:433 for Eros
+*/
+pub fn match_body_num(key: &str) -> Option<i32> {
+  let simple_key = key.trim().to_lowercase();
+  if let Some(num_str) = simple_key.strip_prefix("ast:") {
+    return match num_str.parse::<i32>() {
+      Ok(num) if num > 0 && num < i32::MAX - AST_OFFSET => Some(AST_OFFSET + num),
+      _ => None,
+    };
+  }
+  BODY_KEYS.iter().find(|(k, _, _)| *k == simple_key.as_str()).map(|(_, _, num)| *num)
+}
+
+/*
+* Bodies without a Bodies variant, i.e. numbered asteroids, are calculated by their number
+*/
+pub fn is_numbered_asteroid(key: &str) -> bool {
+  match match_body_num(key) {
+    Some(num) => num > AST_OFFSET,
+    _ => false,
+  }
+}
+
+/*
+* Error message with all valid keys if any keys are unknown
+*/
+pub fn to_unknown_body_keys_error(unknown_keys: &Vec<String>) -> Option<String> {
+  if unknown_keys.len() > 0 {
+    let valid_keys: Vec<String> = BODY_KEYS.iter().map(|(k, name, _)| format!("{} ({})", k, name)).collect();
+    Some(format!("unknown body keys: {}. Valid keys: {}, ast:number (MPC-numbered asteroid, e.g. ast:433 for Eros)", unknown_keys.join(", "), valid_keys.join(", ")))
+  } else {
+    None
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn matches_body_nums() {
+    assert_eq!(match_body_num("pa"), Some(18));
+    assert_eq!(match_body_num("ast:433"), Some(10433));
+    assert_eq!(match_body_num("er"), Some(146199));
+    assert_eq!(match_body_num("xx"), None);
+    assert_eq!(match_body_num("ast:x"), None);
+    assert!(is_numbered_asteroid("se"));
+    assert!(!is_numbered_asteroid("lt"));
+    for (key, _, num) in BODY_KEYS.iter().filter(|(_, _, num)| *num < AST_OFFSET) {
+      assert_eq!(Bodies::from_key(key) as i32, *num);
+    }
+    assert!(to_unknown_body_keys_error(&vec!["xx".to_string()]).unwrap().contains("vs (Vesta)"));
+  }
+}
+
 /* #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct GrahaInfo {
   num: u8,
@@ -0,0 +1,13 @@
+/*
+* Hermetic lots after Paulus Alexandrinus as (key, name, A, B, C) for A + B - C by day.
+* B and C are reversed by night. Later lots may refer to earlier lots
+*/
+pub const LOT_FORMULAS: [(&'static str, &'static str, &'static str, &'static str, &'static str); 7] = [
+  ("fortune", "Part of Fortune", "as", "mo", "su"),
+  ("spirit", "Part of Spirit", "as", "su", "mo"),
+  ("eros", "Part of Eros", "as", "ve", "spirit"),
+  ("necessity", "Part of Necessity", "as", "fortune", "me"),
+  ("courage", "Part of Courage", "as", "fortune", "ma"),
+  ("victory", "Part of Victory", "as", "ju", "spirit"),
+  ("nemesis", "Part of Nemesis", "as", "fortune", "sa"),
+];
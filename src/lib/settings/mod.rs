@@ -1,3 +1,8 @@
 pub mod ayanamshas;
 pub mod graha_values;
-/* pub mod varga_values; */
\ No newline at end of file
+pub mod varga_values;
+pub mod nakshatra_values;
+pub mod sign_values;
+pub mod panchanga_values;
+pub mod lot_values;
+pub mod star_values;
\ No newline at end of file
@@ -0,0 +1,45 @@
+/*
+* The 27 nakshatras from Ashwini at 0º sidereal Aries, each spanning 13º 20'
+*/
+pub const NAKSHATRA_NAMES_27: [&str; 27] = [
+  "ashwini",
+  "bharani",
+  "krittika",
+  "rohini",
+  "mrigashira",
+  "ardra",
+  "punarvasu",
+  "pushya",
+  "ashlesha",
+  "magha",
+  "purva_phalguni",
+  "uttara_phalguni",
+  "hasta",
+  "chitra",
+  "swati",
+  "vishakha",
+  "anuradha",
+  "jyeshtha",
+  "mula",
+  "purva_ashadha",
+  "uttara_ashadha",
+  "shravana",
+  "dhanishtha",
+  "shatabhisha",
+  "purva_bhadrapada",
+  "uttara_bhadrapada",
+  "revati",
+];
+
+/*
+* Vimshottari lords of the 27 nakshatras repeat in this sequence from Ashwini
+*/
+pub const NAKSHATRA_LORDS: [&str; 9] = ["ke", "ve", "su", "mo", "ma", "ra", "ju", "sa", "me"];
+
+/*
+* Abhijit is inserted between Uttara Ashadha and Shravana in the 28 nakshatra system.
+* It spans 276º 40' to 280º 53' 20", shortening its neighbours
+*/
+pub const ABHIJIT_START: f64 = 276.6666666666667;
+pub const ABHIJIT_END: f64 = 280.8888888888889;
+pub const ABHIJIT_INDEX: u8 = 21;
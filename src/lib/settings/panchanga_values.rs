@@ -0,0 +1,75 @@
+/*
+* Tithi names within each paksha. The 15th tithi is purnima in the waxing half and amavasya in the waning half
+*/
+pub const TITHI_NAMES: [&str; 14] = [
+  "pratipada",
+  "dvitiya",
+  "tritiya",
+  "chaturthi",
+  "panchami",
+  "shashthi",
+  "saptami",
+  "ashtami",
+  "navami",
+  "dashami",
+  "ekadashi",
+  "dvadashi",
+  "trayodashi",
+  "chaturdashi",
+];
+
+/*
+* The 27 yogas from the sum of the sidereal Sun and Moon longitudes, each spanning 13º 20'
+*/
+pub const YOGA_NAMES: [&str; 27] = [
+  "vishkambha",
+  "priti",
+  "ayushman",
+  "saubhagya",
+  "shobhana",
+  "atiganda",
+  "sukarma",
+  "dhriti",
+  "shula",
+  "ganda",
+  "vriddhi",
+  "dhruva",
+  "vyaghata",
+  "harshana",
+  "vajra",
+  "siddhi",
+  "vyatipata",
+  "variyana",
+  "parigha",
+  "shiva",
+  "siddha",
+  "sadhya",
+  "shubha",
+  "shukla",
+  "brahma",
+  "indra",
+  "vaidhriti",
+];
+
+/*
+* The 7 movable karanas repeat 8 times from the 2nd to the 57th half-tithi
+*/
+pub const MOVABLE_KARANA_NAMES: [&str; 7] = ["bava", "balava", "kaulava", "taitila", "garaja", "vanija", "vishti"];
+
+/*
+* Fixed karanas for the last 3 half-tithis of the lunar month, with kimstughna for the first
+*/
+pub const FIXED_KARANA_NAMES: [&str; 4] = ["shakuni", "chatushpada", "naga", "kimstughna"];
+
+/*
+* Week days from Sunday with their ruling planets
+*/
+pub const VARA_NAMES: [(&str, &str); 7] = [
+  ("ravivara", "su"),
+  ("somavara", "mo"),
+  ("mangalavara", "ma"),
+  ("budhavara", "me"),
+  ("guruvara", "ju"),
+  ("shukravara", "ve"),
+  ("shanivara", "sa"),
+];
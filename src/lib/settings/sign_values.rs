@@ -0,0 +1,19 @@
+pub const SIGN_NAMES: [&str; 12] = [
+  "aries",
+  "taurus",
+  "gemini",
+  "cancer",
+  "leo",
+  "virgo",
+  "libra",
+  "scorpio",
+  "sagittarius",
+  "capricorn",
+  "aquarius",
+  "pisces",
+];
+
+/*
+* Traditional sign lords from Aries to Pisces
+*/
+pub const SIGN_LORDS: [&str; 12] = ["ma", "ve", "me", "mo", "su", "me", "ve", "ma", "ju", "sa", "sa", "ju"];
@@ -0,0 +1,22 @@
+/*
+* Traditional names of bright and astrologically prominent fixed stars as listed in sefstars.txt,
+* starting with the four royal stars
+*/
+pub const DEFAULT_STARS: [&'static str; 16] = [
+  "Aldebaran",
+  "Regulus",
+  "Antares",
+  "Fomalhaut",
+  "Algol",
+  "Alcyone",
+  "Sirius",
+  "Canopus",
+  "Arcturus",
+  "Vega",
+  "Capella",
+  "Rigel",
+  "Procyon",
+  "Betelgeuse",
+  "Spica",
+  "Pollux",
+];
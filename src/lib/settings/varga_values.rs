@@ -0,0 +1,70 @@
+use serde::{Serialize, Deserialize};
+
+#[derive(Serialize, Deserialize, Debug, Copy, Clone)]
+pub struct VargaItem {
+  pub num: u16,
+  pub key: &'static str,
+  pub name: &'static str,
+}
+
+impl VargaItem {
+  pub const fn new(num: u16, key: &'static str, name: &'static str) -> VargaItem {
+    VargaItem { num, key, name }
+  }
+}
+
+/*
+* The 16 Parashari divisional charts (shodasavarga) including the rasi chart itself
+*/
+pub const VARGA_ITEMS: [VargaItem; 16] = [
+  VargaItem::new(1, "d1", "rasi"),
+  VargaItem::new(2, "d2", "hora"),
+  VargaItem::new(3, "d3", "drekkana"),
+  VargaItem::new(4, "d4", "chaturthamsa"),
+  VargaItem::new(7, "d7", "saptamsa"),
+  VargaItem::new(9, "d9", "navamsa"),
+  VargaItem::new(10, "d10", "dasamsa"),
+  VargaItem::new(12, "d12", "dwadasamsa"),
+  VargaItem::new(16, "d16", "shodasamsa"),
+  VargaItem::new(20, "d20", "vimsamsa"),
+  VargaItem::new(24, "d24", "chaturvimsamsa"),
+  VargaItem::new(27, "d27", "bhamsa"),
+  VargaItem::new(30, "d30", "trimsamsa"),
+  VargaItem::new(40, "d40", "khavedamsa"),
+  VargaItem::new(45, "d45", "akshavedamsa"),
+  VargaItem::new(60, "d60", "shashtiamsa"),
+];
+
+pub fn all_varga_items() -> Vec<VargaItem> {
+  VARGA_ITEMS.to_vec()
+}
+
+pub fn match_varga_item(num: u16) -> Option<VargaItem> {
+  VARGA_ITEMS.iter().find(|v| v.num == num).map(|v| v.to_owned())
+}
+
+/*
+* Unequal trimsamsa segments in odd signs as end degree and zero-based sign index ruled by Mars, Saturn, Jupiter, Mercury and Venus.
+* Even signs have the same segments in reverse order ruled by the other sign of each planet
+*/
+pub const TRIMSAMSA_ODD: [(f64, usize); 5] = [(5f64, 0), (10f64, 10), (18f64, 8), (25f64, 2), (30f64, 6)];
+
+pub const TRIMSAMSA_EVEN: [(f64, usize); 5] = [(5f64, 1), (12f64, 5), (20f64, 11), (25f64, 9), (30f64, 7)];
+
+/*
+* Match varga items from a query string value. all yields all divisional charts,
+* otherwise a comma-separated list of division numbers with or without a d prefix, e.g. 9,10 or d9,d60
+*/
+pub fn match_varga_items(ref_str: &str) -> Vec<VargaItem> {
+  match ref_str.to_lowercase().as_str() {
+    "" | "0" => vec![],
+    "all" => all_varga_items(),
+    _ => ref_str.split(",").filter_map(|k| {
+      let num_str = k.trim().to_lowercase().replace("d", "");
+      match num_str.parse::<u16>() {
+        Ok(num) => match_varga_item(num),
+        _ => None,
+      }
+    }).collect(),
+  }
+}
@@ -0,0 +1,83 @@
+use std::cmp::Ordering;
+use super::{core::{calc_body_dual_jd_topo}, aspect_transits::{normalize_180}};
+use super::models::{geo_pos::GeoPos, graha_pos::{GrahaPos, SkyPos}};
+use super::super::extensions::swe::{azalt_atmosphere, sidtime, Atmosphere};
+
+/*
+* Hour angle in degrees from -180 to 180, positive west of the meridian
+*/
+pub fn calc_hour_angle(jd: f64, geo_lng: f64, ra: f64) -> f64 {
+  normalize_180(sidtime(jd) * 15f64 + geo_lng - ra)
+}
+
+/*
+* Angle between the directions to the zenith and the celestial pole at the object, zero on the meridian
+*/
+pub fn calc_parallactic_angle(hour_angle: f64, geo_lat: f64, dec: f64) -> f64 {
+  let ha = hour_angle.to_radians();
+  let lat = geo_lat.to_radians();
+  let dec = dec.to_radians();
+  ha.sin().atan2(lat.tan() * dec.cos() - dec.sin() * ha.cos()).to_degrees()
+}
+
+/*
+* Relative optical path length through the atmosphere from the apparent altitude after Kasten and Young (1989),
+* 1 at the zenith and about 38 at the horizon. None below the horizon
+*/
+pub fn calc_airmass(apparent_altitude: f64) -> Option<f64> {
+  if apparent_altitude >= 0f64 {
+    Some(1f64 / (apparent_altitude.to_radians().sin() + 0.50572f64 * (apparent_altitude + 6.07995f64).powf(-1.6364f64)))
+  } else {
+    None
+  }
+}
+
+/*
+* Horizontal position of an object by right ascension and declination for the observer's location, altitude and atmospheric conditions
+*/
+pub fn calc_sky_position(jd: f64, geo: GeoPos, atmosphere: Atmosphere, ra: f64, dec: f64) -> SkyPos {
+  let altitude_set = azalt_atmosphere(jd, true, geo, atmosphere, ra, dec);
+  let hour_angle = calc_hour_angle(jd, geo.lng, ra);
+  SkyPos {
+    altitude: altitude_set.value,
+    apparent_altitude: altitude_set.apparent,
+    azimuth: altitude_set.azimuth,
+    hour_angle,
+    parallactic_angle: calc_parallactic_angle(hour_angle, geo.lat, dec),
+    airmass: calc_airmass(altitude_set.apparent),
+  }
+}
+
+/*
+* Topocentric body positions in both coordinate systems with their sky positions
+*/
+pub fn get_bodies_sky_topo(jd: f64, keys: Vec<&str>, geo: GeoPos, atmosphere: Atmosphere, aya_offset: f64) -> Vec<GrahaPos> {
+  keys.into_iter().map(|key| {
+    let pos = calc_body_dual_jd_topo(jd, key, geo, false, aya_offset);
+    pos.with_sky_position(calc_sky_position(jd, geo, atmosphere, pos.rect_ascension, pos.declination))
+  }).collect()
+}
+
+/*
+* Bodies above the horizon altitude by apparent altitude, highest first
+*/
+pub fn get_visible_bodies(jd: f64, keys: Vec<&str>, geo: GeoPos, atmosphere: Atmosphere) -> Vec<GrahaPos> {
+  let mut bodies: Vec<GrahaPos> = get_bodies_sky_topo(jd, keys, geo, atmosphere, 0f64).into_iter().filter(|pos| pos.apparent_altitude().unwrap_or(-90f64) > atmosphere.horizon).collect();
+  bodies.sort_by(|a, b| b.apparent_altitude().partial_cmp(&a.apparent_altitude()).unwrap_or(Ordering::Equal));
+  bodies
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn matches_airmass_and_parallactic_angle() {
+    assert!((calc_airmass(90f64).unwrap() - 1f64).abs() < 0.001f64);
+    assert!((calc_airmass(30f64).unwrap() - 1.995f64).abs() < 0.01f64);
+    assert!(calc_airmass(-1f64).is_none());
+    assert_eq!(calc_parallactic_angle(0f64, 51.5f64, 20f64), 0f64);
+    assert!(calc_parallactic_angle(30f64, 51.5f64, 20f64) > 0f64);
+    assert!(calc_parallactic_angle(-30f64, 51.5f64, 20f64) < 0f64);
+  }
+}
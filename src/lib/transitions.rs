@@ -1,8 +1,8 @@
 use serde::{Serialize, Deserialize};
-use super::super::extensions::swe::{rise_trans};
+use super::super::extensions::swe::{rise_trans, Atmosphere, RiseTransEvent, SweError};
 use libswe_sys::sweconst::{Bodies};
 use libswe_sys::swerust::{handler_swe07::{pheno_ut}};
-use super::{core::{calc_altitude_object, calc_next_prev_horizon}, traits::*, models::{geo_pos::*, general::*, graha_pos::{PhenoResult, PhenoItem}, i_time::*}, transposed_transitions::{calc_transitions_from_source_refs_altitude, calc_transitions_from_source_refs_minmax}, julian_date::{julian_day_to_iso_datetime}};
+use super::{core::{calc_altitude_object, calc_next_prev_horizon}, settings::graha_values::{is_numbered_asteroid}, traits::*, models::{geo_pos::*, general::*, graha_pos::{PhenoResult, PhenoItem}, i_time::*}, transposed_transitions::{calc_transitions_from_source_refs_altitude, calc_transitions_from_source_refs_minmax, calc_sun_altitude_crossings}, julian_date::{julian_day_to_iso_datetime}};
 
 pub enum TransitionParams {
   Rise = 1,
@@ -29,6 +29,13 @@ impl TransitionParams {
     TransitionParams::BitNoRefraction as i32 | TransitionParams::BitGeoctrNoEclLat as i32
   }
 
+  /*
+  * Disc centre without refraction by default, otherwise the upper limb with refraction
+  */
+  pub fn disc_rising(refraction: bool) -> i32 {
+    if refraction { 0 } else { TransitionParams::center_disc_rising() }
+  }
+
   pub fn center_disc_rising_rise() -> i32 {
     TransitionParams::center_disc_rising() | TransitionParams::Rise as i32
   }
@@ -54,6 +61,15 @@ impl TransitionParams {
   }
 }
 
+/*
+* Altitudes of the sun's disc centre at which twilight and the golden and blue hours begin or end
+*/
+pub const ASTRONOMICAL_TWILIGHT_ALT: f64 = -18f64;
+pub const NAUTICAL_TWILIGHT_ALT: f64 = -12f64;
+pub const CIVIL_TWILIGHT_ALT: f64 = -6f64;
+pub const BLUE_HOUR_END_ALT: f64 = -4f64;
+pub const GOLDEN_HOUR_END_ALT: f64 = 6f64;
+
 pub trait TransitionGroup {
   fn period(&self) -> f64;
 
@@ -73,6 +89,8 @@ pub struct ExtendedTransitionSet {
   pub next_rise: f64,
   pub min: f64,
   pub max: f64,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub twilight: Option<TwilightSet>,
 }
 
 impl TransitionGroup for ExtendedTransitionSet {
@@ -84,7 +102,7 @@ impl TransitionGroup for ExtendedTransitionSet {
     let is_up = self.min >= 0f64 && self.max > 0f64;
     let prev_key = if is_up { "prev_rise" } else { "prev_set"};
     let next_key = if is_up { "next_set" } else { "next_rise"};
-    vec![
+    let mut items = vec![
       KeyNumValue::new(prev_key, self.prev_set),
       KeyNumValue::new("rise", self.rise),
       KeyNumValue::new("mc", self.mc),
@@ -93,7 +111,11 @@ impl TransitionGroup for ExtendedTransitionSet {
       KeyNumValue::new(next_key, self.next_rise),
       KeyNumValue::new("min", self.min),
       KeyNumValue::new("max", self.max),
-    ]
+    ];
+    if let Some(twilight) = &self.twilight {
+      items.append(&mut twilight.to_key_nums());
+    }
+    items
   }
 }
 
@@ -126,6 +148,10 @@ impl ExtendedTransitionSet {
     if self.is_up() { 1 } else if self.is_down() { -1 } else { 0 }
   }
 
+  pub fn with_twilight(&self, twilight: TwilightSet) -> ExtendedTransitionSet {
+    ExtendedTransitionSet { twilight: Some(twilight), ..self.to_owned() }
+  }
+
   pub fn as_iso_datetime(&self) -> ExtendedTransitionIsoSet {
     let prev_rise_val = if self.is_up() { self.prev_set } else { 0f64 };
     let prev_set_val = if self.is_up() { 0f64 } else { self.prev_set };
@@ -142,6 +168,7 @@ impl ExtendedTransitionSet {
       next_rise: julian_day_to_iso_datetime(next_rise_val),
       next_set: julian_day_to_iso_datetime(next_set_val),
       max: self.max,
+      twilight: self.twilight.as_ref().map(|twilight| twilight.as_iso_datetime()),
     }
   }
 
@@ -172,6 +199,93 @@ pub struct ExtendedTransitionIsoSet {
   pub next_set: String,
   pub min: f64,
   pub max: f64,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub twilight: Option<TwilightIsoSet>,
+}
+
+/*
+* Times when the sun's disc centre crosses twilight altitudes, rising at dawn and setting at dusk.
+* Blue hours run from civil dawn to the golden dawn and from the golden dusk end to civil dusk,
+* while golden hours run from -4º to 6º. Times are zero where the sun does not cross an altitude, as in polar day or night
+*/
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct TwilightSet {
+  #[serde(rename="astronomicalDawn")]
+  pub astronomical_dawn: f64,
+  #[serde(rename="nauticalDawn")]
+  pub nautical_dawn: f64,
+  #[serde(rename="civilDawn")]
+  pub civil_dawn: f64,
+  #[serde(rename="goldenDawn")]
+  pub golden_dawn: f64,
+  #[serde(rename="goldenDawnEnd")]
+  pub golden_dawn_end: f64,
+  #[serde(rename="goldenDusk")]
+  pub golden_dusk: f64,
+  #[serde(rename="goldenDuskEnd")]
+  pub golden_dusk_end: f64,
+  #[serde(rename="civilDusk")]
+  pub civil_dusk: f64,
+  #[serde(rename="nauticalDusk")]
+  pub nautical_dusk: f64,
+  #[serde(rename="astronomicalDusk")]
+  pub astronomical_dusk: f64,
+}
+
+impl TwilightSet {
+  pub fn as_iso_datetime(&self) -> TwilightIsoSet {
+    TwilightIsoSet {
+      astronomical_dawn: julian_day_to_iso_datetime(self.astronomical_dawn),
+      nautical_dawn: julian_day_to_iso_datetime(self.nautical_dawn),
+      civil_dawn: julian_day_to_iso_datetime(self.civil_dawn),
+      golden_dawn: julian_day_to_iso_datetime(self.golden_dawn),
+      golden_dawn_end: julian_day_to_iso_datetime(self.golden_dawn_end),
+      golden_dusk: julian_day_to_iso_datetime(self.golden_dusk),
+      golden_dusk_end: julian_day_to_iso_datetime(self.golden_dusk_end),
+      civil_dusk: julian_day_to_iso_datetime(self.civil_dusk),
+      nautical_dusk: julian_day_to_iso_datetime(self.nautical_dusk),
+      astronomical_dusk: julian_day_to_iso_datetime(self.astronomical_dusk),
+    }
+  }
+
+  pub fn to_key_nums(&self) -> Vec<KeyNumValue> {
+    vec![
+      KeyNumValue::new("astronomical_dawn", self.astronomical_dawn),
+      KeyNumValue::new("nautical_dawn", self.nautical_dawn),
+      KeyNumValue::new("civil_dawn", self.civil_dawn),
+      KeyNumValue::new("golden_dawn", self.golden_dawn),
+      KeyNumValue::new("golden_dawn_end", self.golden_dawn_end),
+      KeyNumValue::new("golden_dusk", self.golden_dusk),
+      KeyNumValue::new("golden_dusk_end", self.golden_dusk_end),
+      KeyNumValue::new("civil_dusk", self.civil_dusk),
+      KeyNumValue::new("nautical_dusk", self.nautical_dusk),
+      KeyNumValue::new("astronomical_dusk", self.astronomical_dusk),
+    ]
+  }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct TwilightIsoSet {
+  #[serde(rename="astronomicalDawn",skip_serializing_if = "String::is_empty")]
+  pub astronomical_dawn: String,
+  #[serde(rename="nauticalDawn",skip_serializing_if = "String::is_empty")]
+  pub nautical_dawn: String,
+  #[serde(rename="civilDawn",skip_serializing_if = "String::is_empty")]
+  pub civil_dawn: String,
+  #[serde(rename="goldenDawn",skip_serializing_if = "String::is_empty")]
+  pub golden_dawn: String,
+  #[serde(rename="goldenDawnEnd",skip_serializing_if = "String::is_empty")]
+  pub golden_dawn_end: String,
+  #[serde(rename="goldenDusk",skip_serializing_if = "String::is_empty")]
+  pub golden_dusk: String,
+  #[serde(rename="goldenDuskEnd",skip_serializing_if = "String::is_empty")]
+  pub golden_dusk_end: String,
+  #[serde(rename="civilDusk",skip_serializing_if = "String::is_empty")]
+  pub civil_dusk: String,
+  #[serde(rename="nauticalDusk",skip_serializing_if = "String::is_empty")]
+  pub nautical_dusk: String,
+  #[serde(rename="astronomicalDusk",skip_serializing_if = "String::is_empty")]
+  pub astronomical_dusk: String,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -266,27 +380,39 @@ pub struct AltTransitionIsoSet {
   pub max: f64,
 }
 
+/*
+* Events are None if they do not occur within the day, e.g. no rise or set for circumpolar bodies
+*/
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct TransitionSet {
-  pub rise: f64,
-  pub mc: f64,
-  pub set: f64,
-  pub ic: f64,
+  pub rise: Option<f64>,
+  pub mc: Option<f64>,
+  pub set: Option<f64>,
+  pub ic: Option<f64>,
+}
+
+impl TransitionSet {
+  /*
+  * Angle crossings that occur within the day
+  */
+  pub fn to_events(&self) -> Vec<KeyNumValue> {
+    [("rise", self.rise), ("mc", self.mc), ("set", self.set), ("ic", self.ic)].into_iter().filter_map(|(key, jd)| jd.map(|value| KeyNumValue::new(key, value))).collect()
+  }
 }
 
 impl TransitionGroup for TransitionSet {
   fn period(&self) -> f64 {
-    self.set - self.rise
+    self.set.unwrap_or(0f64) - self.rise.unwrap_or(0f64)
   }
 
   fn to_key_nums(&self) -> Vec<KeyNumValue> {
-    let rise_key = if self.rise < 100f64 { "max" } else { "rise" };
-    let set_key = if self.set < 100f64 { "min" } else { "set" };
+    let rise_key = if self.rise.is_none() { "max" } else { "rise" };
+    let set_key = if self.set.is_none() { "min" } else { "set" };
     vec![
-      KeyNumValue::new(rise_key, self.rise),
-      KeyNumValue::new("mc", self.mc),
-      KeyNumValue::new(set_key, self.set),
-      KeyNumValue::new("ic", self.ic),
+      KeyNumValue::new(rise_key, self.rise.unwrap_or(0f64)),
+      KeyNumValue::new("mc", self.mc.unwrap_or(0f64)),
+      KeyNumValue::new(set_key, self.set.unwrap_or(0f64)),
+      KeyNumValue::new("ic", self.ic.unwrap_or(0f64)),
     ]
   }
 }
@@ -295,19 +421,40 @@ pub fn is_near_poles(lat: f64) -> bool {
   lat >= 60f64 || lat <= -60f64
 }
 
-pub fn calc_transition_set_extended_fast(jd: f64, ipl: Bodies, lat: f64, lng: f64) -> ExtendedTransitionSet {
-  let ref_jd = start_jd_geo(jd, lng);
-  let prev_set = next_set(ref_jd - 1f64, ipl, lat, lng);
-  let rise = next_rise(ref_jd, ipl, lat, lng);
-  let set = next_set(ref_jd, ipl, lat, lng);
+/*
+* Julian day of an event, None if the body is circumpolar or never rises, or the Swiss Ephemeris error
+*/
+pub fn to_event_jd(result: Result<RiseTransEvent, SweError>) -> Result<Option<f64>, SweError> {
+  match result {
+    Ok(event) => Ok(Some(event.jd)),
+    Err(SweError::Circumpolar) => Ok(None),
+    Err(error) => Err(error),
+  }
+}
+
+/*
+* The altitude method replaces calculations where the body is circumpolar or never rises, while other errors are returned
+*/
+fn with_altitude_fallback<T>(result: Result<T, SweError>, calc_altitude_set: impl Fn() -> T) -> Result<T, SweError> {
+  match result {
+    Err(SweError::Circumpolar) => Ok(calc_altitude_set()),
+    _ => result,
+  }
+}
+
+pub fn calc_transition_set_extended_fast(jd: f64, ipl: Bodies, geo: GeoPos, atmosphere: Atmosphere) -> Result<ExtendedTransitionSet, SweError> {
+  let ref_jd = start_jd_geo(jd, geo.lng);
+  let prev_set = next_set(ref_jd - 1f64, ipl, geo, atmosphere)?.jd;
+  let rise = next_rise(ref_jd, ipl, geo, atmosphere)?.jd;
+  let set = next_set(ref_jd, ipl, geo, atmosphere)?.jd;
 
   //let mc = next_mc_q(ref_jd, ipl, lat, lng, rise);
-  let mc = next_mc_normal(ref_jd, ipl, lat, lng);
-  let ic = next_ic_normal(ref_jd, ipl, lat, lng);
-  let next_rise = next_rise(set, ipl, lat, lng);
-  let min = calc_altitude_object(ic, false, lat, lng, ipl.to_key());
-  let max = calc_altitude_object(mc, false, lat, lng, ipl.to_key());
-  ExtendedTransitionSet { 
+  let mc = next_mc_normal(ref_jd, ipl, geo, atmosphere)?.jd;
+  let ic = next_ic_normal(ref_jd, ipl, geo, atmosphere)?.jd;
+  let next_rise = next_rise(set, ipl, geo, atmosphere)?.jd;
+  let min = calc_altitude_object(ic, false, geo.lat, geo.lng, ipl.to_key());
+  let max = calc_altitude_object(mc, false, geo.lat, geo.lng, ipl.to_key());
+  Ok(ExtendedTransitionSet { 
     prev_set,
     rise,
     mc,
@@ -316,42 +463,42 @@ pub fn calc_transition_set_extended_fast(jd: f64, ipl: Bodies, lat: f64, lng: f6
     next_rise,
     min,
     max,
-  }
-}
-
-pub fn calc_transition_set_alt_fast(jd: f64, ipl: Bodies, lat: f64, lng: f64) -> AltTransitionSet {
-  let ref_jd = start_jd_geo(jd, lng);
-  let rise = next_rise(ref_jd, ipl, lat, lng);
-  let set = next_set(ref_jd, ipl, lat, lng);
-  let mc = next_mc_normal(ref_jd, ipl, lat, lng);
-  let ic = next_ic_normal(ref_jd, ipl, lat, lng);
-  let min = calc_altitude_object(ic, false, lat, lng, ipl.to_key());
-  let max = calc_altitude_object(mc, false, lat, lng, ipl.to_key());
-  AltTransitionSet { 
+    twilight: None,
+  })
+}
+
+pub fn calc_transition_set_alt_fast(jd: f64, ipl: Bodies, geo: GeoPos, atmosphere: Atmosphere) -> Result<AltTransitionSet, SweError> {
+  let ref_jd = start_jd_geo(jd, geo.lng);
+  let rise = next_rise(ref_jd, ipl, geo, atmosphere)?.jd;
+  let set = next_set(ref_jd, ipl, geo, atmosphere)?.jd;
+  let mc = next_mc_normal(ref_jd, ipl, geo, atmosphere)?.jd;
+  let ic = next_ic_normal(ref_jd, ipl, geo, atmosphere)?.jd;
+  let min = calc_altitude_object(ic, false, geo.lat, geo.lng, ipl.to_key());
+  let max = calc_altitude_object(mc, false, geo.lat, geo.lng, ipl.to_key());
+  Ok(AltTransitionSet { 
     min,
     rise,
     mc,
     set,
     ic,
     max
-  }
+  })
 }
 
-pub fn calc_transition_set_extended_azalt(jd: f64, ipl: Bodies, lat: f64, lng: f64) -> ExtendedTransitionSet {
-  let ref_jd = start_jd_geo(jd, lng);
-  let geo = GeoPos::simple(lat, lng);
+pub fn calc_transition_set_extended_azalt(jd: f64, ipl: Bodies, geo: GeoPos, atmosphere: Atmosphere) -> ExtendedTransitionSet {
+  let ref_jd = start_jd_geo(jd, geo.lng);
   let ref_key = ipl.to_key();
-  let base = calc_transitions_from_source_refs_minmax(ref_jd, ref_key, geo);
+  let base = calc_transitions_from_source_refs_minmax(ref_jd, ref_key, geo, atmosphere);
   /* let prev = calc_transitions_from_source_refs_altitude(ref_jd - 1f64, ref_key, geo);
   let next = calc_transitions_from_source_refs_altitude(ref_jd + 1f64, ref_key, geo); */
-  let prev = calc_transitions_from_source_refs_altitude(ref_jd - 1f64, ref_key, geo);
-  let next = calc_transitions_from_source_refs_altitude(ref_jd + 1f64, ref_key, geo);
-  let mut prev_set = prev.set;
-  let mut next_rise = next.rise;
-  if prev.rise < 100f64 || prev.set < 100f64 {
-    let down = base.min < 0f64 && base.max < 0f64;
-    prev_set = calc_next_prev_horizon(jd, lat, lng, ipl.to_key(), down, false);
-    next_rise = calc_next_prev_horizon(jd, lat, lng, ipl.to_key(), down, true);
+  let prev = calc_transitions_from_source_refs_altitude(ref_jd - 1f64, ref_key, geo, atmosphere);
+  let next = calc_transitions_from_source_refs_altitude(ref_jd + 1f64, ref_key, geo, atmosphere);
+  let mut prev_set = prev.set.unwrap_or(0f64);
+  let mut next_rise = next.rise.unwrap_or(0f64);
+  if prev.rise.is_none() || prev.set.is_none() {
+    let down = base.min < atmosphere.horizon && base.max < atmosphere.horizon;
+    prev_set = calc_next_prev_horizon(jd, geo, atmosphere, ipl.to_key(), down, false);
+    next_rise = calc_next_prev_horizon(jd, geo, atmosphere, ipl.to_key(), down, true);
   }
   ExtendedTransitionSet { 
     prev_set,
@@ -362,139 +509,168 @@ pub fn calc_transition_set_extended_azalt(jd: f64, ipl: Bodies, lat: f64, lng: f
     next_rise,
     min: base.min,
     max: base.max,
+    twilight: None,
   }
 }
 
-pub fn calc_transition_set_alt_azalt(jd: f64, ipl: Bodies, lat: f64, lng: f64) -> AltTransitionSet {
-  let ref_jd = start_jd_geo(jd, lng);
-  let geo = GeoPos::simple(lat, lng);
+pub fn calc_transition_set_alt_azalt(jd: f64, ipl: Bodies, geo: GeoPos, atmosphere: Atmosphere) -> AltTransitionSet {
+  let ref_jd = start_jd_geo(jd, geo.lng);
   let ref_key = ipl.to_key();
-  calc_transitions_from_source_refs_minmax(ref_jd, ref_key, geo)
+  calc_transitions_from_source_refs_minmax(ref_jd, ref_key, geo, atmosphere)
 }
 
-
-pub fn calc_transition_set_extended(jd: f64, ipl: Bodies, lat: f64, lng: f64) -> ExtendedTransitionSet {
-  if is_near_poles(lat) {
-    calc_transition_set_extended_azalt(jd, ipl, lat, lng)
+/*
+* Transitions via swe_rise_trans, except near the poles or where the body is circumpolar or never rises,
+* in which case they are approximated from variations in altitude with max and min values.
+* Any other Swiss Ephemeris error is returned
+*/
+pub fn calc_transition_set_extended(jd: f64, ipl: Bodies, geo: GeoPos, atmosphere: Atmosphere) -> Result<ExtendedTransitionSet, SweError> {
+  let calc_altitude_set = || calc_transition_set_extended_azalt(jd, ipl, geo, atmosphere);
+  if is_near_poles(geo.lat) {
+    Ok(calc_altitude_set())
   } else {
-    calc_transition_set_extended_fast(jd, ipl, lat, lng)
+    with_altitude_fallback(calc_transition_set_extended_fast(jd, ipl, geo, atmosphere), calc_altitude_set)
   }
 }
 
-pub fn calc_transition_set_alt(jd: f64, ipl: Bodies, lat: f64, lng: f64) -> AltTransitionSet {
-  if is_near_poles(lat) {
-    calc_transition_set_alt_azalt(jd, ipl, lat, lng)
+pub fn calc_transition_set_alt(jd: f64, ipl: Bodies, geo: GeoPos, atmosphere: Atmosphere) -> Result<AltTransitionSet, SweError> {
+  let calc_altitude_set = || calc_transition_set_alt_azalt(jd, ipl, geo, atmosphere);
+  if is_near_poles(geo.lat) {
+    Ok(calc_altitude_set())
   } else {
-    calc_transition_set_alt_fast(jd, ipl, lat, lng)
+    with_altitude_fallback(calc_transition_set_alt_fast(jd, ipl, geo, atmosphere), calc_altitude_set)
   }
 }
 
-pub fn calc_transition_set_fast(jd: f64, ipl: Bodies, lat: f64, lng: f64) -> TransitionSet {
-  let ref_jd = start_jd_geo(jd, lng);
-  let rise = next_rise(ref_jd, ipl, lat, lng);
-  let set = next_set(rise, ipl, lat, lng);
+pub fn calc_transition_set_fast(jd: f64, ipl: Bodies, geo: GeoPos, atmosphere: Atmosphere) -> Result<TransitionSet, SweError> {
+  let ref_jd = start_jd_geo(jd, geo.lng);
+  let rise = next_rise(ref_jd, ipl, geo, atmosphere)?.jd;
+  let set = next_set(rise, ipl, geo, atmosphere)?.jd;
   /* let mc = next_mc(ref_jd, ipl, lat, lng);
   let ic = next_ic(ref_jd, ipl, lat, lng); */
   // MC/IC flags have issues via alc_mer_trans when compiled with gcc
   // use median of rise/set with fixed disc instead
-  let mc = next_mc_normal(ref_jd, ipl, lat, lng);
-  let ic = next_ic_normal(mc, ipl, lat, lng);
-  TransitionSet { 
-    rise,
-    mc,
-    set,
-    ic,
-  }
-}
-
-pub fn calc_transition_set(jd: f64, ipl: Bodies, lat: f64, lng: f64) -> TransitionSet {
-  if is_near_poles(lat) {
-    let ref_jd = start_jd_geo(jd, lng);
-    calc_transitions_from_source_refs_altitude(ref_jd, ipl.to_key(), GeoPos::simple(lat, lng))
+  let mc = next_mc_normal(ref_jd, ipl, geo, atmosphere)?.jd;
+  let ic = next_ic_normal(mc, ipl, geo, atmosphere)?.jd;
+  Ok(TransitionSet { 
+    rise: Some(rise),
+    mc: Some(mc),
+    set: Some(set),
+    ic: Some(ic),
+  })
+}
+
+pub fn calc_transition_set(jd: f64, ipl: Bodies, geo: GeoPos, atmosphere: Atmosphere) -> Result<TransitionSet, SweError> {
+  let calc_altitude_set = || calc_transitions_from_source_refs_altitude(start_jd_geo(jd, geo.lng), ipl.to_key(), geo, atmosphere);
+  if is_near_poles(geo.lat) {
+    Ok(calc_altitude_set())
   } else {
-    calc_transition_set_fast(jd, ipl, lat, lng)
+    with_altitude_fallback(calc_transition_set_fast(jd, ipl, geo, atmosphere), calc_altitude_set)
   }
 }
 
-pub fn calc_transition_sun(jd: f64, geo: GeoPos) -> ExtendedTransitionSet {
-  calc_transition_set_extended(jd, Bodies::Sun, geo.lat, geo.lng)
+pub fn calc_transition_sun(jd: f64, geo: GeoPos, atmosphere: Atmosphere) -> Result<ExtendedTransitionSet, SweError> {
+  calc_transition_set_extended(jd, Bodies::Sun, geo, atmosphere).map(|tr_set| tr_set.with_twilight(calc_twilight_set(jd, geo)))
 }
 
-pub fn calc_transitions_sun(jd: f64, days: u16, geo: GeoPos) -> Vec<KeyNumValue> {
+pub fn calc_transitions_sun(jd: f64, days: u16, geo: GeoPos, atmosphere: Atmosphere) -> Result<Vec<KeyNumValue>, SweError> {
   let mut sets: Vec<KeyNumValue> = Vec::new();
   for i in 0..days {
     let ref_jd = jd + i as f64;
-    let items = calc_transition_set_alt(ref_jd, Bodies::Sun, geo.lat, geo.lng).to_key_nums();
+    let mut items = calc_transition_set_alt(ref_jd, Bodies::Sun, geo, atmosphere)?.to_key_nums();
+    items.append(&mut calc_twilight_set(ref_jd, geo).to_key_nums());
     for item in items {
       sets.push(item);
     }
   }
-  sets
+  Ok(sets)
 }
 
-pub fn calc_transition_moon(jd: f64, geo: GeoPos) -> ExtendedTransitionSet {
-  calc_transition_set_extended(jd, Bodies::Moon, geo.lat, geo.lng)
+/*
+* Twilight and golden and blue hours within the day of the referenced time from the sun's sampled altitudes.
+* Near the poles, altitudes beyond the sun's daily range are skipped in polar day or night
+*/
+pub fn calc_twilight_set(jd: f64, geo: GeoPos) -> TwilightSet {
+  let ref_jd = start_jd_geo(jd, geo.lng);
+  let range = if is_near_poles(geo.lat) {
+    let base = calc_transitions_from_source_refs_minmax(ref_jd, "su", geo, Atmosphere::default());
+    Some((base.min, base.max))
+  } else {
+    None
+  };
+  let calc_crossings = |altitude: f64| match range {
+    Some((min, max)) if altitude <= min || altitude >= max => (0f64, 0f64),
+    _ => calc_sun_altitude_crossings(ref_jd, geo, altitude),
+  };
+  let (astronomical_dawn, astronomical_dusk) = calc_crossings(ASTRONOMICAL_TWILIGHT_ALT);
+  let (nautical_dawn, nautical_dusk) = calc_crossings(NAUTICAL_TWILIGHT_ALT);
+  let (civil_dawn, civil_dusk) = calc_crossings(CIVIL_TWILIGHT_ALT);
+  let (golden_dawn, golden_dusk_end) = calc_crossings(BLUE_HOUR_END_ALT);
+  let (golden_dawn_end, golden_dusk) = calc_crossings(GOLDEN_HOUR_END_ALT);
+  TwilightSet {
+    astronomical_dawn,
+    nautical_dawn,
+    civil_dawn,
+    golden_dawn,
+    golden_dawn_end,
+    golden_dusk,
+    golden_dusk_end,
+    civil_dusk,
+    nautical_dusk,
+    astronomical_dusk,
+  }
 }
 
-pub fn calc_transition_body(jd: f64, ipl: Bodies, geo: GeoPos) -> TransitionSet {
-  calc_transition_set(jd, ipl, geo.lat, geo.lng)
+pub fn calc_transition_moon(jd: f64, geo: GeoPos, atmosphere: Atmosphere) -> Result<ExtendedTransitionSet, SweError> {
+  calc_transition_set_extended(jd, Bodies::Moon, geo, atmosphere)
 }
 
-pub fn next_rise(tjd_ut: f64, ipl: Bodies, lat: f64, lng: f64) -> f64 {
-  rise_trans(tjd_ut, ipl, lat, lng, TransitionParams::center_disc_rising_rise())
+pub fn calc_transition_body(jd: f64, ipl: Bodies, geo: GeoPos, atmosphere: Atmosphere) -> Result<TransitionSet, SweError> {
+  calc_transition_set(jd, ipl, geo, atmosphere)
 }
 
-pub fn next_set(tjd_ut: f64, ipl: Bodies, lat: f64, lng: f64) -> f64 {
-  rise_trans(tjd_ut, ipl, lat, lng, TransitionParams::center_disc_rising_set())
+pub fn next_rise(tjd_ut: f64, ipl: Bodies, geo: GeoPos, atmosphere: Atmosphere) -> Result<RiseTransEvent, SweError> {
+  rise_trans(tjd_ut, ipl, geo, atmosphere, TransitionParams::disc_rising(atmosphere.refraction) | TransitionParams::Rise as i32)
 }
 
-pub fn next_rise_normal(tjd_ut: f64, ipl: Bodies, lat: f64, lng: f64) -> f64 {
-  rise_trans(tjd_ut, ipl, lat, lng, TransitionParams::rise_normal())
+pub fn next_set(tjd_ut: f64, ipl: Bodies, geo: GeoPos, atmosphere: Atmosphere) -> Result<RiseTransEvent, SweError> {
+  rise_trans(tjd_ut, ipl, geo, atmosphere, TransitionParams::disc_rising(atmosphere.refraction) | TransitionParams::Set as i32)
 }
 
-pub fn next_set_normal(tjd_ut: f64, ipl: Bodies, lat: f64, lng: f64) -> f64 {
-  rise_trans(tjd_ut, ipl, lat, lng, TransitionParams::set_normal())
+pub fn next_rise_normal(tjd_ut: f64, ipl: Bodies, geo: GeoPos, atmosphere: Atmosphere) -> Result<RiseTransEvent, SweError> {
+  rise_trans(tjd_ut, ipl, geo, atmosphere, TransitionParams::rise_normal())
 }
 
-pub fn next_mc_normal(tjd_ut: f64, ipl: Bodies, lat: f64, lng: f64) -> f64 {
-  let value = next_mc(tjd_ut, ipl, lat, lng);
-  if value >= 1f64 { 
-    value
-  } else {
-    let rise_n = rise_trans(tjd_ut, ipl, lat, lng, TransitionParams::rise_normal());
-    let set_n = rise_trans(rise_n, ipl, lat, lng, TransitionParams::set_normal());
-    (set_n + rise_n) / 2f64
-  }
+pub fn next_set_normal(tjd_ut: f64, ipl: Bodies, geo: GeoPos, atmosphere: Atmosphere) -> Result<RiseTransEvent, SweError> {
+  rise_trans(tjd_ut, ipl, geo, atmosphere, TransitionParams::set_normal())
 }
 
-pub fn next_ic_normal(tjd_ut: f64, ipl: Bodies, lat: f64, lng: f64) -> f64 {
-  let value = next_ic(tjd_ut, ipl, lat, lng);
-  if value >= 1f64 { 
-    value
-  } else {
-    let set_n = rise_trans(tjd_ut, ipl, lat, lng, TransitionParams::set_normal());
-    let next_rise_n = rise_trans(set_n, ipl, lat, lng, TransitionParams::rise_normal());
-    (next_rise_n + set_n) / 2f64
-  }
+/*
+* Midpoints between rise and set serve as fallback for the MC and IC
+*/
+pub fn next_mc_normal(tjd_ut: f64, ipl: Bodies, geo: GeoPos, atmosphere: Atmosphere) -> Result<RiseTransEvent, SweError> {
+  next_mc(tjd_ut, ipl, geo, atmosphere).or_else(|_| {
+    let rise_n = next_rise_normal(tjd_ut, ipl, geo, atmosphere)?.jd;
+    let set_n = next_set_normal(rise_n, ipl, geo, atmosphere)?.jd;
+    Ok(RiseTransEvent { jd: (set_n + rise_n) / 2f64 })
+  })
 }
 
-pub fn next_mc(tjd_ut: f64, ipl: Bodies, lat: f64, lng: f64) -> f64 {
-  rise_trans(tjd_ut, ipl, lat, lng, TransitionParams::mc())
+pub fn next_ic_normal(tjd_ut: f64, ipl: Bodies, geo: GeoPos, atmosphere: Atmosphere) -> Result<RiseTransEvent, SweError> {
+  next_ic(tjd_ut, ipl, geo, atmosphere).or_else(|_| {
+    let set_n = next_set_normal(tjd_ut, ipl, geo, atmosphere)?.jd;
+    let next_rise_n = next_rise_normal(set_n, ipl, geo, atmosphere)?.jd;
+    Ok(RiseTransEvent { jd: (next_rise_n + set_n) / 2f64 })
+  })
 }
 
-/* pub fn next_mc_q(tjd_ut: f64, ipl: Bodies, lat: f64, lng: f64, rise_jd: f64) -> f64 {
-let set_jd = rise_trans(tjd_ut, ipl, lat, lng, TransitionParams::Set as i32);
-((set_jd - rise_jd) / 2f64) + rise_jd
-} */
-
-pub fn next_ic(tjd_ut: f64, ipl: Bodies, lat: f64, lng: f64) -> f64 {
-  rise_trans(tjd_ut, ipl, lat, lng, TransitionParams::ic())
+pub fn next_mc(tjd_ut: f64, ipl: Bodies, geo: GeoPos, atmosphere: Atmosphere) -> Result<RiseTransEvent, SweError> {
+  rise_trans(tjd_ut, ipl, geo, atmosphere, TransitionParams::mc())
 }
 
-/* pub fn next_ic_q(tjd_ut: f64, ipl: Bodies, lat: f64, lng: f64, set_jd: f64) -> f64 {
-  let next_rise_jd = rise_trans(tjd_ut + 1f64, ipl, lat, lng, TransitionParams::Rise as i32);
-  ((next_rise_jd - set_jd) / 2f64) + set_jd
-} */
+pub fn next_ic(tjd_ut: f64, ipl: Bodies, geo: GeoPos, atmosphere: Atmosphere) -> Result<RiseTransEvent, SweError> {
+  rise_trans(tjd_ut, ipl, geo, atmosphere, TransitionParams::ic())
+}
 
 pub fn start_jd_geo(jd: f64, lng: f64) -> f64 {
   let offset = (0f64 - lng / 15f64) / 24f64;
@@ -514,30 +690,43 @@ pub fn start_jd_geo(jd: f64, lng: f64) -> f64 {
   }
 }
 
-pub fn get_transition_sets(jd: f64, keys: Vec<&str>, geo: GeoPos) -> Vec<KeyNumValueSet> {
+/*
+* Swiss Ephemeris error prefixed with the body key
+*/
+fn with_body_key(key: &str, error: SweError) -> SweError {
+  match error {
+    SweError::Failed(message) => SweError::Failed(format!("{}: {}", key, message)),
+    _ => error,
+  }
+}
+
+/*
+* Transitions are only available for bodies with a Bodies variant, i.e. not numbered asteroids
+*/
+pub fn get_transition_sets(jd: f64, keys: Vec<&str>, geo: GeoPos, atmosphere: Atmosphere) -> Result<Vec<KeyNumValueSet>, SweError> {
   let mut transit_sets: Vec<KeyNumValueSet> = Vec::new();
-  for key in keys {
+  for key in keys.into_iter().filter(|k| !is_numbered_asteroid(k)) {
     let tr_set: Vec<KeyNumValue> = match key {
-      "su" | "mo" => calc_transition_set_extended(jd, Bodies::from_key(key), geo.lat, geo.lng).to_key_nums(),
-      _ => calc_transition_set(jd, Bodies::from_key(key), geo.lat, geo.lng).to_key_nums(),
+      "su" | "mo" => calc_transition_set_extended(jd, Bodies::from_key(key), geo, atmosphere).map_err(|e| with_body_key(key, e))?.to_key_nums(),
+      _ => calc_transition_set(jd, Bodies::from_key(key), geo, atmosphere).map_err(|e| with_body_key(key, e))?.to_key_nums(),
     };
     transit_sets.push(KeyNumValueSet::new(key, tr_set));
   }
-  transit_sets
+  Ok(transit_sets)
 }
 
-pub fn get_transition_sets_extended(jd: f64, keys: Vec<String>, geo: GeoPos, days: u16) -> Vec<KeyNumValueSet> {
+pub fn get_transition_sets_extended(jd: f64, keys: Vec<String>, geo: GeoPos, atmosphere: Atmosphere, days: u16) -> Result<Vec<KeyNumValueSet>, SweError> {
   let mut transit_sets: Vec<KeyNumValueSet> = Vec::new();
-  for key in keys {
+  for key in keys.into_iter().filter(|k| !is_numbered_asteroid(k)) {
     let mut tr_set: Vec<KeyNumValue> = Vec::new();
     for i in 0..days {
       let ref_jd = jd + i as f64;
-      let mut tr_set_day = calc_transition_set_alt(ref_jd, Bodies::from_key(key.as_str()), geo.lat, geo.lng).to_key_nums();
+      let mut tr_set_day = calc_transition_set_alt(ref_jd, Bodies::from_key(key.as_str()), geo, atmosphere).map_err(|e| with_body_key(key.as_str(), e))?.to_key_nums();
       tr_set.append(&mut tr_set_day);
     }
     transit_sets.push(KeyNumValueSet::new(key.as_str(), tr_set));
   }
-  transit_sets
+  Ok(transit_sets)
 }
 
 
@@ -549,7 +738,7 @@ pub fn get_pheno_result(jd: f64, key: &str, iflag: i32) -> PhenoResult {
 
 pub fn get_pheno_results(jd: f64, keys: Vec<&str>) -> Vec<PhenoItem> {
   let mut items: Vec<PhenoItem> = Vec::new();
-  for key in keys {
+  for key in keys.into_iter().filter(|k| !is_numbered_asteroid(k)) {
     let ipl = Bodies::from_key(key);
     let result = pheno_ut(jd, ipl, 0i32);
     let item = PhenoItem::new_from_result(key, result);
@@ -558,10 +747,10 @@ pub fn get_pheno_results(jd: f64, keys: Vec<&str>) -> Vec<PhenoItem> {
   items
 }
 
-pub fn to_indian_time_with_transitions(jd: f64, geo: GeoPos, offset_tz_secs: Option<i16>, iso_mode: bool) -> (ITime, AltTransitionValueSet, AltTransitionValueSet, AltTransitionValueSet, i16) {
-  let current = calc_transition_set_extended(jd, Bodies::from_key("su"), geo.lat, geo.lng);
-  let prev = calc_transition_set_alt(jd - 1f64, Bodies::from_key("su"), geo.lat, geo.lng);
-  let next = calc_transition_set_alt(jd + 1f64, Bodies::from_key("su"), geo.lat, geo.lng);
+pub fn to_indian_time_with_transitions(jd: f64, geo: GeoPos, atmosphere: Atmosphere, offset_tz_secs: Option<i16>, iso_mode: bool) -> Result<(ITime, AltTransitionValueSet, AltTransitionValueSet, AltTransitionValueSet, i16), SweError> {
+  let current = calc_transition_set_extended(jd, Bodies::from_key("su"), geo, atmosphere)?;
+  let prev = calc_transition_set_alt(jd - 1f64, Bodies::from_key("su"), geo, atmosphere)?;
+  let next = calc_transition_set_alt(jd + 1f64, Bodies::from_key("su"), geo, atmosphere)?;
   let prev_start = match prev.start_mode() {
     -1 => prev.mc,
     1 => prev.ic,
@@ -584,18 +773,18 @@ pub fn to_indian_time_with_transitions(jd: f64, geo: GeoPos, offset_tz_secs: Opt
     _ => next.rise,
   };
   let offset_secs = if offset_tz_secs != None { offset_tz_secs.unwrap() } else { (geo.lng * 240f64) as i16 };
-  (ITime::new(jd, prev_start, base_start, base_set, next_start, current.start_mode(), offset_secs), prev.to_value_set(iso_mode), current.to_value_set(iso_mode), next.to_value_set(iso_mode), offset_secs)
+  Ok((ITime::new(jd, prev_start, base_start, base_set, next_start, current.start_mode(), offset_secs), prev.to_value_set(iso_mode), current.to_value_set(iso_mode), next.to_value_set(iso_mode), offset_secs))
 }
 
-pub fn to_indian_time(jd: f64, geo: GeoPos, offset_tz_secs: Option<i16>, iso_mode: bool) -> ITime {
-  let (i_time, _, _, _, _) = to_indian_time_with_transitions(jd, geo, offset_tz_secs, iso_mode);
-  i_time
+pub fn to_indian_time(jd: f64, geo: GeoPos, atmosphere: Atmosphere, offset_tz_secs: Option<i16>, iso_mode: bool) -> Result<ITime, SweError> {
+  let (i_time, _, _, _, _) = to_indian_time_with_transitions(jd, geo, atmosphere, offset_tz_secs, iso_mode)?;
+  Ok(i_time)
 }
 
 
 #[cfg(test)]
 mod tests {
-  use super::start_jd_geo;
+  use super::{start_jd_geo, to_event_jd, RiseTransEvent, SweError, TransitionParams, TwilightSet};
     #[test]
     fn has_correct_geo_day_start_offset() {
         let ref_jd = 2459731.875;
@@ -609,4 +798,26 @@ mod tests {
         assert_eq!(start_jd1, expected_start_1);
         assert_eq!(start_jd2, expected_start_2);
     }
+
+    #[test]
+    fn has_zero_jd_without_event() {
+        assert_eq!(to_event_jd(Ok(RiseTransEvent { jd: 2459731.5 })), Ok(Some(2459731.5)));
+        assert_eq!(to_event_jd(Err(SweError::Circumpolar)), Ok(None));
+        assert_eq!(to_event_jd(Err(SweError::Failed("error".to_string()))), Err(SweError::Failed("error".to_string())));
+    }
+
+    #[test]
+    fn matches_disc_rising_flags() {
+        assert_eq!(TransitionParams::disc_rising(false), 256 | 512 | 128);
+        assert_eq!(TransitionParams::disc_rising(true) | TransitionParams::Rise as i32, 1);
+    }
+
+    #[test]
+    fn omits_missing_twilight_times() {
+        let twilight = TwilightSet { astronomical_dawn: 0f64, nautical_dawn: 0f64, civil_dawn: 2459731.6, golden_dawn: 2459731.61, golden_dawn_end: 2459731.65, golden_dusk: 2459732.2, golden_dusk_end: 2459732.24, civil_dusk: 2459732.25, nautical_dusk: 0f64, astronomical_dusk: 0f64 };
+        let iso = twilight.as_iso_datetime();
+        assert_eq!(iso.astronomical_dawn, "");
+        assert_eq!(iso.civil_dawn.len(), 19);
+        assert_eq!(twilight.to_key_nums().iter().filter(|item| item.value != 0f64).count(), 6);
+    }
 }
\ No newline at end of file
@@ -2,7 +2,8 @@ use serde::{Serialize, Deserialize};
 use super::julian_date::*;
 use super::models::{geo_pos::*, graha_pos::*};
 use super::{models::{general::{KeyNumValue, KeyNumValueSet}}};
-use super::{core::{calc_altitude, calc_body_jd_geo, calc_body_jd_topo}, transitions::{TransitionSet, AltTransitionSet, get_pheno_result}};
+use super::{core::{calc_altitude, calc_altitude_atmosphere, calc_body_jd_geo, calc_body_jd_topo}, transitions::{TransitionSet, AltTransitionSet, get_pheno_result}};
+use super::super::extensions::swe::{Atmosphere};
 
 const MINS_PER_DAY: i32 = 1440;
 
@@ -163,7 +164,8 @@ pub fn calc_transposed_object_transitions (
   multiplier: u8,
   filter: TransitionFilter,
   sample_key: &str,
-  rise_set_minmax: bool
+  rise_set_minmax: bool,
+  atmosphere: Atmosphere,
 ) -> Vec<AltitudeSample> {
   let max = MINS_PER_DAY / multiplier as i32 + 1;
   let mut items: Vec<AltitudeSample> = Vec::new();
@@ -178,12 +180,20 @@ pub fn calc_transposed_object_transitions (
   let mut prev_value = 0f64;
   let mut prev_min = 0f64;
   let mut prev_jd = 0f64;
-  // resample the longitude and latitude speed for the moon only
-  let mut disc_offset = 0f64;
+  // altitudes at which the sun or moon rise and set, relative to the horizon, with the upper limb if refraction applies
+  let mut rise_offset = 0f64;
+  let mut set_offset = 0f64;
   if sample_key == "su" || sample_key == "mo" {
     let pheno = get_pheno_result(jd_start, sample_key, 0i32);
-    disc_offset = pheno.apparent_diameter_of_disc / 4f64;
+    if atmosphere.refraction {
+      rise_offset = pheno.apparent_diameter_of_disc / 2f64;
+      set_offset = rise_offset;
+    } else {
+      rise_offset = pheno.apparent_diameter_of_disc / 4f64;
+      set_offset = 0f64 - rise_offset;
+    }
   }
+  // resample the longitude and latitude speed for the moon only
   let resample_speed = sample_key == "mo" && lng_speed != 0f64;
   for i in 0..max {
     let n = i as f64 * multiplier as f64;
@@ -198,7 +208,7 @@ pub fn calc_transposed_object_transitions (
     }
     let adjusted_lng = if lng_speed != 0f64  { lng + sample_spd * day_frac } else { lng };
     let adjusted_lat = if lat_spd != 0f64 { lat + lat_spd * day_frac } else { lat };
-    let value = calc_altitude(jd, false, geo.lat, geo.lng, adjusted_lng, adjusted_lat);
+    let value = calc_altitude_atmosphere(jd, false, geo, atmosphere, adjusted_lng, adjusted_lat);
 
     let mut item = AltitudeSample::new("", n,jd, value);
     if match_mc && value > mc.value {
@@ -209,13 +219,13 @@ pub fn calc_transposed_object_transitions (
       item.set_mode("ic");
       ic = item.clone();
     }
-    let offset_pv = prev_value + disc_offset;
-    let offset_v = value + disc_offset;
-    let offset_pv2 = prev_value - disc_offset;
-    let offset_v2 = value - disc_offset;
-    if match_rise && offset_pv < 0f64 && offset_v > 0f64 {
+    let offset_pv = prev_value - atmosphere.horizon + rise_offset;
+    let offset_v = value - atmosphere.horizon + rise_offset;
+    let offset_pv2 = prev_value - atmosphere.horizon + set_offset;
+    let offset_v2 = value - atmosphere.horizon + set_offset;
+    if i > 0 && match_rise && offset_pv < 0f64 && offset_v > 0f64 {
       rise = calc_mid_sample(item.clone(), prev_min, offset_pv, prev_jd, "rise");
-    } else if match_set && offset_pv2 > 0f64 && offset_v2 < 0f64 {
+    } else if i > 0 && match_set && offset_pv2 > 0f64 && offset_v2 < 0f64 {
       set = calc_mid_sample(item.clone(), prev_min, offset_pv2, prev_jd, "set");
     }
     if !match_mc && !match_ic {
@@ -238,11 +248,11 @@ pub fn calc_transposed_object_transitions (
   }
   if rise_set_minmax {
     if rise.jd <= 0f64 { 
-      let rise_jd = if mc.value > 0f64 { 0f64 } else { mc.value };
+      let rise_jd = if mc.value > atmosphere.horizon { 0f64 } else { mc.value };
       rise = AltitudeSample::new("rise", 0f64, rise_jd, mc.value - ic.value);
     }
     if set.jd <= 0f64 { 
-      let set_jd = if mc.value > 0f64 { ic.value } else { 0f64 };
+      let set_jd = if mc.value > atmosphere.horizon { ic.value } else { 0f64 };
       set = AltitudeSample::new("set", 0f64, set_jd, mc.value - ic.value);
     }
   }
@@ -265,7 +275,8 @@ pub fn calc_transposed_graha_transition(
     multiplier,
     filter,
     graha_pos.key.as_str(),
-    true
+    true,
+    Atmosphere::default()
   )
 }
 
@@ -273,36 +284,9 @@ pub fn calc_transposed_graha_transition(
   Calculate transposed transitions from a set of pre-calculated real celestial body positions
   This is useful when working with existing chart data for things like natal transitions
 */
-pub fn calc_transposed_graha_transitions_from_source_positions(jd_start: f64, geo: GeoPos, graha_positions: Vec<GrahaPos>) -> Vec<KeyNumValueSet> {
+pub fn calc_transposed_graha_transitions_from_source_positions(jd_start: f64, geo: GeoPos, graha_positions: Vec<GrahaPos>, days: u16) -> Vec<KeyNumValueSet> {
   let mut key_num_sets: Vec<KeyNumValueSet> = Vec::new();
   for graha_pos in graha_positions {
-    let tr_samples: Vec<AltitudeSample> = calc_transposed_object_transitions(
-      jd_start,
-      geo,
-      graha_pos.lng,
-      graha_pos.lat,
-      graha_pos.lng_speed,
-      5,
-      TransitionFilter::All,
-      graha_pos.key.as_str(),
-      true
-    );
-    let tr_key_set: KeyNumValueSet = KeyNumValueSet::new(graha_pos.key.as_str(), tr_samples.iter().map(|tr| tr.to_key_num()).collect());
-    key_num_sets.push(tr_key_set);
-  }
-  key_num_sets
-}
-
-/*
-  Calculate transposed transitions from a set of real body positions with a different time and place
-*/
-pub fn calc_transposed_graha_transitions_from_source_refs(mode: &str, jd_start: f64, geo: GeoPos, jd_historic: f64, geo_historic: GeoPos, keys: Vec<String>, days: u16) -> Vec<KeyNumValueSet> {
-  let mut key_num_sets: Vec<KeyNumValueSet> = Vec::new();
-  for key in keys {
-    let graha_pos = match mode {
-      "topo" => calc_body_jd_topo(jd_historic, key.as_str(), geo_historic, 0f64),
-      _ => calc_body_jd_geo(jd_historic, key.as_str(), 0f64)
-    };
     let mut items: Vec<KeyNumValue> = Vec::new();
     for i in 0..days {
       let ref_jd = jd_start + i as f64;
@@ -315,7 +299,8 @@ pub fn calc_transposed_graha_transitions_from_source_refs(mode: &str, jd_start:
         5,
         TransitionFilter::All,
         graha_pos.key.as_str(),
-        true
+        true,
+        Atmosphere::default()
       );
       let mut new_items: Vec<KeyNumValue> = tr_samples.iter().map(|tr| tr.to_key_num()).collect();
       items.append(&mut new_items);
@@ -326,35 +311,62 @@ pub fn calc_transposed_graha_transitions_from_source_refs(mode: &str, jd_start:
   key_num_sets
 }
 
+/*
+  Calculate transposed transitions from a set of real body positions with a different time and place
+*/
+pub fn calc_transposed_graha_transitions_from_source_refs(mode: &str, jd_start: f64, geo: GeoPos, jd_historic: f64, geo_historic: GeoPos, keys: Vec<String>, days: u16) -> Vec<KeyNumValueSet> {
+  let graha_positions: Vec<GrahaPos> = keys.iter().map(|key| match mode {
+    "topo" => calc_body_jd_topo(jd_historic, key.as_str(), geo_historic, 0f64),
+    _ => calc_body_jd_geo(jd_historic, key.as_str(), 0f64)
+  }).collect();
+  calc_transposed_graha_transitions_from_source_positions(jd_start, geo, graha_positions, days)
+}
+
 fn extract_from_alt_samples(alt_samples: &Vec<AltitudeSample>, key: &str) -> AltitudeSample {
   alt_samples.into_iter().find(|sample| sample.mode.as_str() == key).unwrap_or(&AltitudeSample::basic(key)).to_owned()
 }
 
+/*
+* Time of the sampled event, None if it does not occur
+*/
+fn extract_event_jd(alt_samples: &Vec<AltitudeSample>, key: &str) -> Option<f64> {
+  alt_samples.iter().find(|sample| sample.mode.as_str() == key).map(|sample| sample.jd)
+}
+
 
 /**
  * Alternative method to fetch transitions for near polar latitudes (> +60 and < -60) based on altitudes
 */
-pub fn calc_transitions_from_source_refs_altitude(jd: f64, key: &str, geo: GeoPos) -> TransitionSet {
+pub fn calc_transitions_from_source_refs_altitude(jd: f64, key: &str, geo: GeoPos, atmosphere: Atmosphere) -> TransitionSet {
   let pos = calc_body_jd_topo(jd, key, geo, 0f64);
-  let alt_samples = calc_transposed_object_transitions(jd, geo, pos.lng, pos.lat, pos.lng_speed, 5, TransitionFilter::All, key, true);
-  let rise = extract_from_alt_samples(&alt_samples, "rise");
-  let set = extract_from_alt_samples(&alt_samples, "set");
-  let mc = extract_from_alt_samples(&alt_samples, "mc");
-  let ic = extract_from_alt_samples(&alt_samples, "ic");
+  let alt_samples = calc_transposed_object_transitions(jd, geo, pos.lng, pos.lat, pos.lng_speed, 5, TransitionFilter::All, key, true, atmosphere);
   TransitionSet { 
-    rise: rise.jd,
-    mc: mc.jd,
-    set: set.jd,
-    ic: ic.jd,
+    rise: extract_event_jd(&alt_samples, "rise"),
+    mc: extract_event_jd(&alt_samples, "mc"),
+    set: extract_event_jd(&alt_samples, "set"),
+    ic: extract_event_jd(&alt_samples, "ic"),
   }
 }
 
+/**
+ * Times when the sun's disc centre rises above and sets below an altitude within the day from jd, e.g. -6 for civil twilight.
+ * Zero where the sun does not cross the altitude
+*/
+pub fn calc_sun_altitude_crossings(jd: f64, geo: GeoPos, altitude: f64) -> (f64, f64) {
+  let pos = calc_body_jd_topo(jd, "su", geo, 0f64);
+  // without a sample key no disc size offset applies
+  let alt_samples = calc_transposed_object_transitions(jd, geo, pos.lng, pos.lat, pos.lng_speed, 5, TransitionFilter::RiseSet, "", false, Atmosphere::default().with_horizon(altitude));
+  let rise = extract_from_alt_samples(&alt_samples, "rise");
+  let set = extract_from_alt_samples(&alt_samples, "set");
+  (rise.jd, set.jd)
+}
+
 /**
  * Alternative method to fetch transitions for near polar latitudes (> +60 and < -60) with min and max altitudes
 */
-pub fn calc_transitions_from_source_refs_minmax(jd: f64, key: &str, geo: GeoPos) -> AltTransitionSet {
+pub fn calc_transitions_from_source_refs_minmax(jd: f64, key: &str, geo: GeoPos, atmosphere: Atmosphere) -> AltTransitionSet {
   let pos = calc_body_jd_topo(jd, key, geo, 0f64);
-  let alt_samples = calc_transposed_object_transitions(jd, geo, pos.lng, pos.lat, pos.lng_speed, 5, TransitionFilter::All, key, false);
+  let alt_samples = calc_transposed_object_transitions(jd, geo, pos.lng, pos.lat, pos.lng_speed, 5, TransitionFilter::All, key, false, atmosphere);
   let rise = extract_from_alt_samples(&alt_samples, "rise");
   let set = extract_from_alt_samples(&alt_samples, "set");
   let mc = extract_from_alt_samples(&alt_samples, "mc");
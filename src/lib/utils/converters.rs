@@ -1,17 +1,52 @@
+use std::collections::HashMap;
 use super::super::models::geo_pos::{GeoPos};
+use super::super::settings::graha_values::{match_body_num};
 
 pub fn to_str_refs(strings: &Vec<String>) -> Vec<&str> {
   let strs: Vec<&str> = strings.iter().map(|s| s.as_ref()).collect();
   strs
 }
 
+/*
+* Body keys in a comma-separated list, normalised to lower case, with the all and core group keys expanded
+*/
 pub fn body_keys_str_to_keys(key_string: String) -> Vec<String> {
-  key_string.split(",").into_iter().filter(|p| p.len() == 2).map(|p| p.to_string()).collect()
+  let mut keys: Vec<String> = Vec::new();
+  for key in key_string.split(",").into_iter().map(|p| p.trim().to_lowercase()) {
+    let group_keys = match_body_group_keys(key.as_str());
+    let matched_keys = if group_keys.len() > 0 { group_keys } else if key.len() == 2 || key.starts_with("ast:") { vec![key.as_str()] } else { vec![] };
+    for matched_key in matched_keys {
+      if !keys.iter().any(|k| k.as_str() == matched_key) {
+        keys.push(matched_key.to_string());
+      }
+    }
+  }
+  keys
+}
+
+/*
+* all: planets from Mercury to Pluto except Earth with the Sun, Moon, Rahu and Ketu
+* core: bodies used in traditional astrology, Sun, Moon, Mars, Mercury, Jupiter, Venus, Saturn, Rahu and Ketu
+*/
+pub fn match_body_group_keys(key: &str) -> Vec<&'static str> {
+  match key {
+    "all" => vec!["su", "mo", "me", "ve", "ma", "ju", "sa", "ur", "ne", "pl", "ra", "ke"],
+    "core" => vec!["su", "mo", "ma", "me", "ju", "ve", "sa", "ra", "ke"],
+    _ => vec![],
+  }
 }
 
 pub fn body_keys_str_to_keys_or(key_string: String, default_keys: Vec<&str>) -> Vec<String> {
   let keys: Vec<String> = body_keys_str_to_keys(key_string);
-  if keys.len() > 0 { keys.into_iter().filter(|k| k.len() == 2 && !k.contains("as")).collect() } else { default_keys.into_iter().map(|p| p.to_string() ).collect() }
+  if keys.len() > 0 { keys.into_iter().filter(|k| k.as_str() != "as").collect() } else { default_keys.into_iter().map(|p| p.to_string() ).collect() }
+}
+
+/*
+* Keys in a comma-separated list that do not match any body. The ascendant (as) is accepted where bodies may include it,
+* as are the all and core group keys
+*/
+pub fn match_unknown_body_keys(key_string: &str) -> Vec<String> {
+  key_string.split(",").into_iter().map(|p| p.trim().to_lowercase()).filter(|p| p.len() > 0 && p.as_str() != "as" && match_body_group_keys(p).len() < 1 && match_body_num(p).is_none()).collect()
 }
 
 pub fn loc_string_to_geo(loc: &str) -> Option<GeoPos> {
@@ -22,4 +57,34 @@ pub fn loc_string_to_geo(loc: &str) -> Option<GeoPos> {
   } else {
     None
   }
-}
\ No newline at end of file
+}
+
+/*
+* Parse comma-separated key:value pairs, e.g. su:10,mo:8, into a map of keys and numbers
+*/
+pub fn key_num_str_to_map(ref_str: &str) -> HashMap<String, f64> {
+  let mut items: HashMap<String, f64> = HashMap::new();
+  for pair in ref_str.split(",") {
+    let parts: Vec<&str> = pair.split(":").collect();
+    if parts.len() == 2 {
+      if let Ok(value) = parts[1].trim().parse::<f64>() {
+        items.insert(parts[0].trim().to_lowercase(), value);
+      }
+    }
+  }
+  items
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn matches_body_keys_by_group_and_case() {
+    assert_eq!(body_keys_str_to_keys("Su, AST:433,xyz".to_string()), vec!["su", "ast:433"]);
+    assert_eq!(body_keys_str_to_keys("core,ur".to_string()).len(), 10);
+    assert_eq!(body_keys_str_to_keys("all,su".to_string()).len(), 12);
+    assert!(match_unknown_body_keys("AST:433,Core,as").is_empty());
+    assert_eq!(match_unknown_body_keys("su,xx"), vec!["xx"]);
+  }
+}
@@ -16,20 +16,16 @@ extern crate chrono;
 use libswe_sys::sweconst::{
     Bodies, Calandar, OptionalFlag,
 }; */
-use std::{thread, time};
-use libswe_sys::swerust::{
-    handler_swe02::*,
-};
 use serde::{Serialize, Deserialize};
 use serde_json::*;
 use clap::Parser;
 use lib::{models::{date_info::*}};
-use extensions::swe::{set_sid_mode};
+use extensions::ephemeris::{init_ephemeris};
 use actix_web::{App, HttpServer, Responder, web::{self, Json}};
 use std::path::Path;
 use constants::*;
 use help::*;
-use routes::{chart_data::*, transitions::*, planet_stations::*, datetime::*, progress_synastry::*};
+use routes::{chart_data::*, transitions::*, planet_stations::*, datetime::*, progress_synastry::*, aspects::*, dasha::*, panchanga::*, eclipses::*, moon_phases::*, ingresses::*, directions::*, returns::*, relationships::*, midpoints::*, stars::*, sky::*};
 
 /// Astrologic engine config
 #[derive(Parser, Debug)]
@@ -68,13 +64,6 @@ fn get_ephemeris_path() -> String {
   args.ephemeris
 }
 
-pub fn reset_ephemeris_path() {
-  let micro_interval = time::Duration::from_millis(10);
-  let ep = get_ephemeris_path();
-  set_ephe_path(ep.as_str());
-  thread::sleep(micro_interval);
-}
-
 #[actix_web::main]
 async fn main()  -> std::io::Result<()> {
   
@@ -83,8 +72,7 @@ async fn main()  -> std::io::Result<()> {
     let port = args.port as u16;
     let has_path = Path::new(&ephemeris_path).exists();
     if  has_path {
-      set_ephe_path(ephemeris_path.as_str());
-      set_sid_mode(0);
+      init_ephemeris(ephemeris_path.as_str());
     }
 
     HttpServer::new(move || {
@@ -98,6 +86,7 @@ async fn main()  -> std::io::Result<()> {
           .service(bodies_progress)
           .service(body_positions)
           .service(chart_data_flexi)
+          .service(chart_data_from_post)
           .service(progress_synastry_items)
           .service(list_sun_transitions)
           .service(pheno_data)
@@ -107,6 +96,22 @@ async fn main()  -> std::io::Result<()> {
           .service(body_transposed_transitions_range)
           .service(planet_stations_progress)
           .service(body_transposed_transitions_from_chart)
+          .service(synastry_aspects)
+          .service(transit_aspects)
+          .service(transit_aspects_from_chart)
+          .service(vimshottari_dasha)
+          .service(panchanga_data)
+          .service(eclipses_range)
+          .service(moon_phases_range)
+          .service(body_ingresses)
+          .service(chart_directions)
+          .service(body_returns)
+          .service(composite_chart)
+          .service(davison_chart)
+          .service(chart_midpoints)
+          .service(chart_harmonics)
+          .service(fixed_stars)
+          .service(sky_bodies)
           .route("/{sec1}", web::get().to(route_not_found))
           .route("/{sec1}/{sec2}", web::get().to(route_not_found))
           .route("/{sec1}/{sec2}/{sec3}", web::get().to(route_not_found))
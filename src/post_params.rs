@@ -0,0 +1,100 @@
+use serde::{Serialize, Deserialize};
+use std::collections::HashMap;
+use super::lib::{models::{date_info::DateInfo, geo_pos::GeoPos, graha_pos::GrahaPos, general::KeyNumValue}, lots::LotFormula, julian_date::{current_datetime_string}, core::{match_numbered_asteroid_error}, settings::graha_values::{to_unknown_body_keys_error}, utils::converters::{body_keys_str_to_keys, loc_string_to_geo, match_unknown_body_keys}};
+use super::query_params::InputOptions;
+
+/**
+ * Pre-calculated body position as supplied by a client, e.g. from a saved chart
+ */
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PostBodyPos {
+  pub key: String,
+  pub lng: f64,
+  #[serde(default)]
+  pub lat: f64,
+  #[serde(rename="lngSpeed",default)]
+  pub lng_speed: f64,
+}
+
+impl PostBodyPos {
+  pub fn to_graha_pos(&self) -> GrahaPos {
+    GrahaPos::new_geo(self.key.as_str(), self.lng, self.lat, self.lng_speed)
+  }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PostOptions {
+  pub dt: Option<String>, // current UTC date string
+  pub jd: Option<f64>, // current jd as a float
+  pub loc: Option<String>, // current comma-separated lat,lng(,alt) numeric string
+  pub days: Option<u16>, // number of days from the current date
+  pub years: Option<u16>, // number of years from the current date, overrides days where applicable
+  pub iso: Option<u8>, // 0 show JD, 1 show ISO UTC
+  pub bodies: Option<Vec<PostBodyPos>>, // full body positions with key, lng, lat and lngSpeed
+  pub lngs: Option<Vec<KeyNumValue>>, // key/value longitudes only
+  pub positions: Option<HashMap<String, f64>>, // longitudes keyed by body key as returned by /positions
+  #[serde(rename="transitBodies")]
+  pub transit_bodies: Option<String>, // comma-separated transiting body keys
+  pub aspects: Option<String>, // major, all or comma-separated aspect keys
+}
+
+impl PostOptions {
+
+  pub fn to_date_object(&self) -> DateInfo {
+    let jd = self.jd.unwrap_or(0f64);
+    if jd > 1_000_000f64 {
+      DateInfo::new_from_jd(jd)
+    } else {
+      let dateref: String = self.dt.clone().unwrap_or(current_datetime_string());
+      DateInfo::new(dateref.as_str())
+    }
+  }
+
+  /**
+   * Error listing all valid keys if the transiting bodies include unknown body keys,
+   * or the Swiss Ephemeris error if a numbered asteroid cannot be calculated
+   */
+  pub fn body_keys_error(&self) -> Option<String> {
+    let key_string = self.transit_bodies.clone().unwrap_or("".to_string());
+    to_unknown_body_keys_error(&match_unknown_body_keys(key_string.as_str()))
+      .or_else(|| match_numbered_asteroid_error(self.to_date_object().jd, &body_keys_str_to_keys(key_string)))
+  }
+
+  pub fn to_geo(&self) -> GeoPos {
+    let loc: String = self.loc.clone().unwrap_or("0,0".to_string());
+    if let Some(geo_pos) = loc_string_to_geo(loc.as_str()) { geo_pos } else { GeoPos::zero() }
+  }
+
+  /**
+   * Merge all supplied position formats into one set of body positions.
+   * Full body positions take precedence over bare longitudes with the same key
+   */
+  pub fn to_graha_positions(&self) -> Vec<GrahaPos> {
+    let mut items: Vec<GrahaPos> = self.bodies.clone().unwrap_or(vec![]).iter().map(|b| b.to_graha_pos()).collect();
+    let mut extra_lngs: Vec<KeyNumValue> = self.lngs.clone().unwrap_or(vec![]);
+    if let Some(positions) = self.positions.clone() {
+      let mut keys: Vec<String> = positions.keys().map(|k| k.to_owned()).collect();
+      keys.sort();
+      for key in keys {
+        extra_lngs.push(KeyNumValue::new(key.as_str(), positions.get(&key).unwrap().to_owned()));
+      }
+    }
+    for item in extra_lngs {
+      if !items.iter().any(|g| g.key == item.key) {
+        items.push(GrahaPos::basic(item.key.as_str(), item.value));
+      }
+    }
+    items
+  }
+}
+
+/**
+ * All /chart-data options with optional lot formulas that replace or extend the default lots
+ */
+#[derive(Deserialize)]
+pub struct PostChartOptions {
+  #[serde(flatten)]
+  pub options: InputOptions,
+  #[serde(rename="lotFormulas")]
+  pub lot_formulas: Option<Vec<LotFormula>>,
+}
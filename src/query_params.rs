@@ -1,6 +1,6 @@
 use serde::{Deserialize};
-use actix_web::{web::{Query} };
-use super::lib::{models::date_info::DateInfo, julian_date::{current_datetime_string}};
+use super::lib::{models::date_info::DateInfo, julian_date::{current_datetime_string}, core::{match_numbered_asteroid_error}, settings::graha_values::{to_unknown_body_keys_error}, utils::converters::{body_keys_str_to_keys, match_unknown_body_keys}};
+use super::extensions::swe::{Atmosphere};
 
 #[derive(Deserialize)]
 pub struct InputOptions {
@@ -37,9 +37,61 @@ pub struct InputOptions {
   pub retro: Option<u8>, // show planet stations (retrograde, peak), 0 no, 1 yes
   pub iso: Option<u8>, // 0 show JD, 1 show ISO UTC
   pub tzs: Option<i16>, // offset in seconds from UTC
+  pub aspects: Option<String>, // 1 or major: major aspects, all: major and minor aspects, or comma-separated aspect keys
+  pub orbs: Option<String>, // comma-separated body orb overrides, e.g. su:10,mo:8
+  pub bodies2: Option<String>, // secondary body keys, e.g. natal bodies for transit aspects
+  pub dasha: Option<u8>, // number of vimshottari dasha levels, 1 mahadashas, 2 with antardashas, 3 with pratyantardashas
+  pub yt: Option<String>, // year type: tropical, sidereal or anomalistic
+  pub meta: Option<u8>, // 0 none, 1 show sign, nakshatra, pada and lords for each body and the ascendant
+  pub nak: Option<u8>, // number of nakshatras, 27 (default) or 28 with Abhijit
+  pub vargas: Option<String>, // all or comma-separated divisional chart numbers, e.g. 9,10,60
+  pub ecl: Option<String>, // eclipse kind: solar, lunar or both (default)
+  pub ingress: Option<String>, // sign (default), nakshatra or comma-separated degrees
+  pub pm: Option<String>, // progression method for angles: sa (solar arc, default) or naibod
+  pub dm: Option<String>, // direction methods: sa (solar arc, default), placidus, regiomontanus, primary or all
+  #[serde(rename="type")]
+  pub return_type: Option<String>, // return type: solar (default), lunar or a body key
+  pub year: Option<i32>, // calendar year
+  pub month: Option<u8>, // calendar month 1-12 within the year
+  pub dial: Option<f64>, // midpoint dial in degrees: 360, 90 (default), 45 or 22.5
+  pub orb: Option<f64>, // orb in degrees
+  pub harmonics: Option<String>, // comma-separated harmonic numbers or ranges, e.g. 5,7,9 or 1-12
+  pub lots: Option<String>, // 1 or all for all lots, or comma-separated lot keys, e.g. fortune,spirit
+  pub stars: Option<String>, // 1 or all for the default fixed stars, or comma-separated star names, e.g. Regulus,Spica
+  pub parans: Option<u8>, // 0: no parans, 1: show parans between stars and bodies
+  pub pres: Option<f64>, // atmospheric pressure in hPa (mbar) for refraction, 0 (default) estimates it from the altitude
+  pub temp: Option<f64>, // temperature in ºC for refraction, default 0
+  pub hor: Option<f64>, // horizon altitude in degrees for rise and set, e.g. -6 for civil twilight, default 0
+  pub refr: Option<u8>, // 0: disc centre without refraction (default), 1: upper limb with refraction
 }
 
-pub fn to_ayanamsha_keys(params: &Query<InputOptions>, def_val: &str) -> (Vec<String>, String) {
+impl InputOptions {
+  /*
+  * Error listing all valid keys if bodies, bodies2 or p2bodies include unknown body keys,
+  * or the Swiss Ephemeris error if a numbered asteroid cannot be calculated
+  */
+  pub fn body_keys_error(&self) -> Option<String> {
+    let mut unknown_keys: Vec<String> = Vec::new();
+    for key_string in [&self.bodies, &self.bodies2, &self.p2bodies] {
+      unknown_keys.append(&mut match_unknown_body_keys(key_string.clone().unwrap_or("".to_string()).as_str()));
+    }
+    if let Some(error) = to_unknown_body_keys_error(&unknown_keys) {
+      return Some(error);
+    }
+    let keys: Vec<String> = [&self.bodies, &self.p2bodies].into_iter().flat_map(|key_string| body_keys_str_to_keys(key_string.clone().unwrap_or("".to_string()))).collect();
+    let keys_2 = body_keys_str_to_keys(self.bodies2.clone().unwrap_or("".to_string()));
+    match_numbered_asteroid_error(to_date_object(self).jd, &keys).or_else(|| match_numbered_asteroid_error(to_date_object_2(self).jd, &keys_2))
+  }
+
+  /*
+  * Atmospheric conditions and horizon altitude for rise and set times
+  */
+  pub fn to_atmosphere(&self) -> Atmosphere {
+    Atmosphere::new(self.pres.unwrap_or(0f64), self.temp.unwrap_or(0f64), self.hor.unwrap_or(0f64), self.refr.unwrap_or(0) > 0)
+  }
+}
+
+pub fn to_ayanamsha_keys(params: &InputOptions, def_val: &str) -> (Vec<String>, String) {
   let aya: String = params.aya.clone().unwrap_or(def_val.to_string());
   
   let aya_keys = match aya.as_str() {
@@ -54,7 +106,7 @@ pub fn to_ayanamsha_keys(params: &Query<InputOptions>, def_val: &str) -> (Vec<St
   (aya_keys, mode)
 }
 
-pub fn to_date_object_by_num(params: &Query<InputOptions>, num: u8) -> DateInfo {
+pub fn to_date_object_by_num(params: &InputOptions, num: u8) -> DateInfo {
   let jd = match num {
     2 => params.jd2.clone().unwrap_or(0f64),
     _ => params.jd.clone().unwrap_or(0f64)
@@ -70,10 +122,21 @@ pub fn to_date_object_by_num(params: &Query<InputOptions>, num: u8) -> DateInfo
   }
 }
 
-pub fn to_date_object(params: &Query<InputOptions>) -> DateInfo {
-  to_date_object_by_num(&params, 1)
+pub fn to_date_object(params: &InputOptions) -> DateInfo {
+  to_date_object_by_num(params, 1)
+}
+
+pub fn to_date_object_2(params: &InputOptions) -> DateInfo {
+  to_date_object_by_num(params, 2)
 }
 
-pub fn to_date_object_2(params: &Query<InputOptions>) -> DateInfo {
-  to_date_object_by_num(&params, 2)
+pub const MAX_SPAN_DAYS: f64 = 36525f64;
+
+/*
+* Span in days from optional days or years, the latter taking precedence, limited to 100 years
+*/
+pub fn to_span_days(days: Option<u16>, years: Option<u16>, def_days: u16) -> f64 {
+  let num_years = years.unwrap_or(0);
+  let num_days = if num_years > 0 { num_years as f64 * 365.25f64 } else { days.unwrap_or(def_days) as f64 };
+  if num_days > MAX_SPAN_DAYS { MAX_SPAN_DAYS } else if num_days < 1f64 { 1f64 } else { num_days }
 }
@@ -0,0 +1,76 @@
+use serde_json::*;
+use actix_web::{get, post, Responder,web::{Query, Json}};
+use super::super::lib::{aspects::*, aspect_transits::*, models::{geo_pos::*, date_info::*, graha_pos::BodyPos}, utils::{converters::*}};
+use super::super::{query_params::*, post_params::*, extensions::ephemeris::{lock_ephemeris}};
+
+#[get("/aspects")]
+async fn synastry_aspects(params: Query<InputOptions>) -> impl Responder {
+  let _ephemeris = lock_ephemeris();
+  if let Some(error) = params.body_keys_error() {
+    return Json(json!({ "valid": false, "error": error }));
+  }
+  let date = to_date_object(&params);
+  let date2 = to_date_object_2(&params);
+  let loc: String = params.loc.clone().unwrap_or("0,0".to_string());
+  let geo = if let Some(geo_pos) = loc_string_to_geo(loc.as_str()) { geo_pos } else { GeoPos::zero() };
+  let loc2: String = params.loc2.clone().unwrap_or("0,0".to_string());
+  let geo2 = if let Some(geo_pos) = loc_string_to_geo(loc2.as_str()) { geo_pos } else { GeoPos::zero() };
+  let def_keys = vec!["su", "mo", "ma", "me", "ju", "ve", "sa", "ur", "ne", "pl"];
+  let key_string: String = params.bodies.clone().unwrap_or("".to_string());
+  let keys = body_keys_str_to_keys_or(key_string, def_keys);
+  let topo: bool = params.topo.clone().unwrap_or(0) > 0;
+  let aspect_types = match_aspect_types(params.aspects.clone().unwrap_or("major".to_string()).as_str());
+  let aspect_orbs = AspectOrbs::new(key_num_str_to_map(params.orbs.clone().unwrap_or("".to_string()).as_str()));
+  let positions1 = calc_chart_aspect_positions(date.jd, geo, &keys, topo);
+  let positions2 = calc_chart_aspect_positions(date2.jd, geo2, &keys, topo);
+  let aspects = calc_cross_aspects(&positions1, &positions2, &aspect_types, &aspect_orbs);
+  let valid = positions1.len() > 0 && positions2.len() > 0;
+  Json(json!({ "valid": valid, "date": date, "geo": geo, "date2": date2, "geo2": geo2, "aspects": aspects }))
+}
+
+#[get("/transit-aspects")]
+async fn transit_aspects(params: Query<InputOptions>) -> impl Responder {
+  let _ephemeris = lock_ephemeris();
+  if let Some(error) = params.body_keys_error() {
+    return Json(json!({ "valid": false, "error": error }));
+  }
+  let date = to_date_object(&params);
+  let natal_date = to_date_object_2(&params);
+  let loc2: String = params.loc2.clone().unwrap_or("0,0".to_string());
+  let natal_geo = if let Some(geo_pos) = loc_string_to_geo(loc2.as_str()) { geo_pos } else { GeoPos::zero() };
+  let def_keys = vec!["ju", "sa", "ur", "ne", "pl"];
+  let key_string: String = params.bodies.clone().unwrap_or("".to_string());
+  let keys = body_keys_str_to_keys_or(key_string, def_keys);
+  let def_natal_keys = vec!["su", "mo", "me", "ve", "ma", "ju", "sa"];
+  let natal_key_string: String = params.bodies2.clone().unwrap_or("".to_string());
+  let natal_keys = body_keys_str_to_keys_or(natal_key_string, def_natal_keys);
+  let iso_mode: bool = params.iso.clone().unwrap_or(0) > 0;
+  let aspect_types = match_aspect_types(params.aspects.clone().unwrap_or("major".to_string()).as_str());
+  let span_days = to_span_days(params.days, params.years, 366);
+  let end_date = DateInfo::new_from_jd(date.jd + span_days);
+  let natal_positions = calc_chart_aspect_positions(natal_date.jd, natal_geo, &natal_keys, false);
+  let items = calc_transit_aspects(to_str_refs(&keys), &natal_positions, &aspect_types, date.jd, end_date.jd, iso_mode);
+  let valid = natal_positions.len() > 0;
+  Json(json!({ "valid": valid, "start": date, "end": end_date, "natalDate": natal_date, "natalGeo": natal_geo, "natalPositions": natal_positions.iter().map(|p| p.to_body("ecl")).collect::<Vec<BodyPos>>(), "items": items }))
+}
+
+#[post("/transit-aspects")]
+async fn transit_aspects_from_chart(payload: Json<PostOptions>) -> impl Responder {
+  let _ephemeris = lock_ephemeris();
+  let params = payload.into_inner();
+  if let Some(error) = params.body_keys_error() {
+    return Json(json!({ "valid": false, "error": error }));
+  }
+  let date = params.to_date_object();
+  let def_keys = vec!["ju", "sa", "ur", "ne", "pl"];
+  let key_string: String = params.transit_bodies.clone().unwrap_or("".to_string());
+  let keys = body_keys_str_to_keys_or(key_string, def_keys);
+  let iso_mode: bool = params.iso.unwrap_or(0) > 0;
+  let aspect_types = match_aspect_types(params.aspects.clone().unwrap_or("major".to_string()).as_str());
+  let span_days = to_span_days(params.days, params.years, 366);
+  let end_date = DateInfo::new_from_jd(date.jd + span_days);
+  let natal_positions = params.to_graha_positions();
+  let items = calc_transit_aspects(to_str_refs(&keys), &natal_positions, &aspect_types, date.jd, end_date.jd, iso_mode);
+  let valid = natal_positions.len() > 0;
+  Json(json!({ "valid": valid, "start": date, "end": end_date, "natalPositions": natal_positions.iter().map(|p| p.to_body("ecl")).collect::<Vec<BodyPos>>(), "items": items }))
+}
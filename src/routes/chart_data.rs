@@ -1,15 +1,13 @@
-use std::{thread, time};
 use serde_json::*;
-use super::super::lib::{core::*,  transitions::*, models::{geo_pos::*, graha_pos::*, houses::*, date_info::*, general::*, i_time::*}, utils::{converters::*}, settings::{ayanamshas::{match_ayanamsha_key}}, planet_stations::{match_all_nextprev_planet_stations, BodySpeedSet}};
-use actix_web::{get, Responder,web::{Query, Json} };
+use super::super::lib::{core::*,  transitions::*, models::{geo_pos::*, graha_pos::*, houses::*, date_info::*, general::*, i_time::*, body_meta::*, varga::*}, utils::{converters::*}, settings::{ayanamshas::{match_ayanamsha_key}, varga_values::{match_varga_items}, graha_values::{to_unknown_body_keys_error}}, planet_stations::{match_all_nextprev_planet_stations, BodySpeedSet}, aspects::*, dasha::*, progressions::{match_progression_method}, moon_phases::{calc_moon_phase_state}, lots::*, fixed_stars::{match_star_names, calc_star_positions, get_star_transition_sets, FixedStarPos}, sky::{get_bodies_sky_topo}};
+use actix_web::{get, post, Responder,web::{Query, Json} };
 use super::super::lib::julian_date::{current_year};
-use super::super::{query_params::*};
+use super::super::{query_params::*, post_params::*, extensions::{ephemeris::{lock_ephemeris}, swe::{SweError}}};
 use serde::{Serialize, Deserialize};
-use super::super::{reset_ephemeris_path};
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
-struct ChartDataResult {
-  valid: bool,
+pub struct ChartDataResult {
+  pub valid: bool,
   date: DateInfo,
   geo: GeoPos,
   #[serde(rename="indianTime",skip_serializing_if = "Option::is_none")]
@@ -27,13 +25,29 @@ struct ChartDataResult {
   #[serde(skip_serializing_if = "Vec::is_empty")]
   pheno: Vec<PhenoItem>,
   #[serde(rename="planetStations",skip_serializing_if = "Vec::is_empty")]
-  planet_stations: Vec<BodySpeedSet>
+  planet_stations: Vec<BodySpeedSet>,
+  #[serde(skip_serializing_if = "Vec::is_empty")]
+  aspects: Vec<AspectResult>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  dasha: Option<DashaSet>,
+  #[serde(skip_serializing_if = "Vec::is_empty")]
+  meta: Vec<BodyMeta>,
+  #[serde(skip_serializing_if = "Vec::is_empty")]
+  vargas: Vec<VargaSet>,
+  #[serde(skip_serializing_if = "Vec::is_empty")]
+  lots: Vec<LotPos>,
+  #[serde(skip_serializing_if = "Vec::is_empty")]
+  stars: Vec<FixedStarPos>,
+  #[serde(rename="unknownStars",skip_serializing_if = "Vec::is_empty")]
+  unknown_stars: Vec<KeyStringValue>,
 }
 
 #[get("/positions")]
 async fn body_positions(params: Query<InputOptions>) -> impl Responder {
-  reset_ephemeris_path();
-  let micro_interval = time::Duration::from_millis(20);
+  let _ephemeris = lock_ephemeris();
+  if let Some(error) = params.body_keys_error() {
+    return Json(json!({ "valid": false, "error": error }));
+  }
   let date = to_date_object(&params);
   let loc: String = params.loc.clone().unwrap_or("0,0".to_string());
   let geo = if let Some(geo_pos) = loc_string_to_geo(loc.as_str()) { geo_pos } else { GeoPos::zero() };
@@ -58,23 +72,77 @@ async fn body_positions(params: Query<InputOptions>) -> impl Responder {
       _ => get_body_longitudes_geo(date.jd, geo, aya_offset, to_str_refs(&keys))
     }
   };
+  let show_meta: bool = params.meta.unwrap_or(0) > 0;
+  let num_nakshatras: u8 = params.nak.unwrap_or(27);
+  let meta = if show_meta {
+    let ecl_longitudes = match eq {
+      1 => match topo {
+        1 => get_body_longitudes_topo(date.jd, geo, aya_offset, to_str_refs(&keys)),
+        _ => get_body_longitudes_geo(date.jd, geo, aya_offset, to_str_refs(&keys))
+      },
+      _ => longitudes.clone(),
+    };
+    let mut lng_items: Vec<(String, f64)> = ecl_longitudes.into_iter().collect();
+    lng_items.sort_by(|a, b| a.0.cmp(&b.0));
+    to_body_meta_items(lng_items, ayanamsha - aya_offset, num_nakshatras)
+  } else { vec![] };
   let valid = longitudes.len() > 0;
-  let sun_transitions = calc_transition_sun(date.jd, geo).to_value_set(iso_mode);
-  let moon_transitions = calc_transition_moon(date.jd, geo).to_value_set(iso_mode);
+  let (sun_transitions, moon_transitions) = match (calc_transition_sun(date.jd, geo, params.to_atmosphere()), calc_transition_moon(date.jd, geo, params.to_atmosphere())) {
+    (Ok(sun), Ok(moon)) => (sun.to_value_set(iso_mode), moon.to_value_set(iso_mode)),
+    (Err(error), _) | (_, Err(error)) => return Json(json!({ "valid": false, "error": error.to_string() })),
+  };
+  let moon_phase = calc_moon_phase_state(date.jd);
   let coord_system = build_coord_system_label(eq > 0, topo > 0);
-  thread::sleep(micro_interval);
-  Json(json!({ "valid": valid, "date": date, "geo": geo, "longitudes": longitudes, "ayanamsha": { "key": aya_key, "value": ayanamsha, "applied": sidereal }, "coordinateSystem": coord_system, "sunTransitions": sun_transitions, "moonTransitions": moon_transitions }))
+  Json(json!({ "valid": valid, "date": date, "geo": geo, "longitudes": longitudes, "ayanamsha": { "key": aya_key, "value": ayanamsha, "applied": sidereal }, "coordinateSystem": coord_system, "sunTransitions": sun_transitions, "moonTransitions": moon_transitions, "moonPhase": moon_phase, "meta": meta }))
 }
 
 #[get("/chart-data")]
 pub async fn chart_data_flexi(params: Query<InputOptions>) -> impl Responder {
-  reset_ephemeris_path();
-  let micro_interval = time::Duration::from_millis(50);
+  let _ephemeris = lock_ephemeris();
+  if let Some(error) = params.body_keys_error() {
+    return Json(json!({ "valid": false, "error": error }));
+  }
   let date = to_date_object(&params);
   let loc: String = params.loc.clone().unwrap_or("0,0".to_string());
   let geo = if let Some(geo_pos) = loc_string_to_geo(loc.as_str()) { geo_pos } else { GeoPos::zero() };
+  match build_chart_data(date, geo, &params, &vec![]) {
+    Ok(result) => Json(json!(result)),
+    Err(error) => Json(json!({ "valid": false, "error": error.to_string() })),
+  }
+}
+
+#[post("/chart-data")]
+pub async fn chart_data_from_post(payload: Json<PostChartOptions>) -> impl Responder {
+  let _ephemeris = lock_ephemeris();
+  let params = &payload.options;
+  if let Some(error) = params.body_keys_error() {
+    return Json(json!({ "valid": false, "error": error }));
+  }
+  let date = to_date_object(params);
+  let loc: String = params.loc.clone().unwrap_or("0,0".to_string());
+  let geo = if let Some(geo_pos) = loc_string_to_geo(loc.as_str()) { geo_pos } else { GeoPos::zero() };
+  let lot_formulas: Vec<LotFormula> = payload.lot_formulas.clone().unwrap_or(vec![]).iter().map(|f| f.to_lower_case()).collect();
+  let merged_formulas = merge_lot_formulas(&lot_formulas);
+  if let Some(error) = to_unknown_body_keys_error(&match_unknown_lot_point_keys(&merged_formulas)) {
+    return Json(json!({ "valid": false, "error": error }));
+  }
+  let point_keys: Vec<String> = merged_formulas.iter().flat_map(|f| f.point_keys()).map(|k| k.to_string()).collect();
+  if let Some(error) = match_numbered_asteroid_error(date.jd, &point_keys) {
+    return Json(json!({ "valid": false, "error": error }));
+  }
+  match build_chart_data(date, geo, params, &lot_formulas) {
+    Ok(result) => Json(json!(result)),
+    Err(error) => Json(json!({ "valid": false, "error": error.to_string() })),
+  }
+}
+
+/*
+* Chart data with all options of /chart-data for the referenced time and location.
+* Custom lot formulas replace or extend the default lots. Swiss Ephemeris errors in transitions are returned
+*/
+pub fn build_chart_data(date: DateInfo, geo: GeoPos, params: &InputOptions, custom_lots: &Vec<LotFormula>) -> std::result::Result<ChartDataResult, SweError> {
   let show_transitions: bool = params.ct.clone().unwrap_or(0) > 0;
-  let (aya_keys, aya_mode) = to_ayanamsha_keys(&params, "true_citra");
+  let (aya_keys, aya_mode) = to_ayanamsha_keys(params, "true_citra");
   let hsys_str = params.hsys.clone().unwrap_or("W".to_string());
   let match_all_houses = hsys_str.to_lowercase().as_str() == "all";
   let h_systems: Vec<char> = if match_all_houses { vec![] } else { match_house_systems_chars(hsys_str) };
@@ -99,10 +167,13 @@ pub async fn chart_data_flexi(params: Query<InputOptions>) -> impl Responder {
   let sidereal: bool = params.sid.unwrap_or(0) > 0;
   let ayanamsha = get_ayanamsha_value(date.jd, aya.as_str());
   let aya_offset = if sidereal { ayanamsha } else { 0f64 };
+  let aspect_types = match_aspect_types(params.aspects.clone().unwrap_or("".to_string()).as_str());
+  let aspect_orbs = AspectOrbs::new(key_num_str_to_map(params.orbs.clone().unwrap_or("".to_string()).as_str()));
   let data = match topo {
     1 => match eq {
       0 => get_bodies_ecl_topo(date.jd, to_str_refs(&keys), geo, aya_offset),
       1 => get_bodies_eq_topo(date.jd, to_str_refs(&keys), geo),
+      3 => get_bodies_sky_topo(date.jd, to_str_refs(&keys), geo, params.to_atmosphere(), aya_offset),
       _ => get_bodies_dual_topo(date.jd, to_str_refs(&keys), geo, show_pheno_inline, aya_offset),
     }
     _ => match eq {
@@ -127,28 +198,71 @@ pub async fn chart_data_flexi(params: Query<InputOptions>) -> impl Responder {
     _ => get_ayanamsha_values(date.jd, to_str_refs(&aya_keys)),
   };
   
-  let transition_jds: Vec<KeyNumValueSet> = if show_transitions { get_transition_sets(date.jd, to_str_refs(&keys), geo) } else { Vec::new() };
+  let aspects = if aspect_types.len() > 0 {
+    let mut aspect_positions = match eq {
+      1 => get_bodies_ecl_geo(date.jd, to_str_refs(&keys), aya_offset_val),
+      _ => data.clone(),
+    };
+    aspect_positions.append(&mut asc_mc_to_graha_positions(&house.points));
+    calc_aspects(&aspect_positions, &aspect_types, &aspect_orbs)
+  } else { vec![] };
+  let show_meta: bool = params.meta.unwrap_or(0) > 0;
+  let num_nakshatras: u8 = params.nak.unwrap_or(27);
+  let varga_items = match_varga_items(params.vargas.clone().unwrap_or("".to_string()).as_str());
+  let lots_str = params.lots.clone().unwrap_or(if custom_lots.len() > 0 { "all".to_string() } else { "".to_string() });
+  let show_lots = lots_str.len() > 0 && lots_str.as_str() != "0";
+  let lng_items: Vec<(String, f64)> = if show_meta || varga_items.len() > 0 || show_lots {
+    let ecl_positions = match eq {
+      1 => get_bodies_ecl_geo(date.jd, to_str_refs(&keys), aya_offset_val),
+      _ => data.clone(),
+    };
+    let mut items: Vec<(String, f64)> = vec![("as".to_string(), house.points.ascendant)];
+    items.append(&mut ecl_positions.into_iter().map(|p| (p.key, p.lng)).collect());
+    items
+  } else { vec![] };
+  let meta = if show_meta { to_body_meta_items(lng_items.clone(), ayanamsha - aya_offset_val, num_nakshatras) } else { vec![] };
+  let lots = if show_lots {
+    let formulas = merge_lot_formulas(custom_lots);
+    let mut point_items = lng_items.clone();
+    point_items.push(("mc".to_string(), house.points.mc));
+    let points = to_lot_points(date.jd, &point_items, &formulas, aya_offset_val);
+    let cusps = if house.sets.len() > 0 { house.sets[0].houses.clone() } else { vec![] };
+    filter_lots(calc_lots(&formulas, &points, is_day_chart(date.jd, geo), &cusps), lots_str.as_str())
+  } else { vec![] };
+  let vargas = if varga_items.len() > 0 { to_varga_sets(lng_items, ayanamsha - aya_offset_val, &varga_items) } else { vec![] };
+  let star_names = match_star_names(params.stars.clone().unwrap_or("".to_string()).as_str());
+  let (stars, unknown_stars) = calc_star_positions(date.jd, &star_names, aya_offset_val);
+  let mut transition_jds: Vec<KeyNumValueSet> = if show_transitions { get_transition_sets(date.jd, to_str_refs(&keys), geo, params.to_atmosphere())? } else { Vec::new() };
+  if show_transitions && stars.len() > 0 {
+    transition_jds.append(&mut get_star_transition_sets(date.jd, &stars.iter().map(|s| s.key.clone()).collect(), geo)?);
+  }
   let transitions: Vec<KeyFlexiValueSet> = transition_jds.iter().map(|item| item.as_flexi_values(iso_mode)).collect();
   let available_p2_keys = vec!["as", "su", "mo", "ma", "me", "ju", "ve", "sa"];
   let p2keys:Vec<String> = keys.clone().iter().filter(|k| available_p2_keys.contains(&k.as_str())).map(|s| s.to_owned()).collect();
-  let p2: Vec<ProgressItemSet> = if show_p2 { get_bodies_p2(date.jd, p2keys, p2_start_year, p2_years as u16, p2_per_year) } else { Vec::new() };
 
   let bodies: FlexiBodyPos = match eq {
     0 => FlexiBodyPos::Simple(data.iter().map(|b| b.to_body("ecl")).collect()),
     1 => FlexiBodyPos::Simple(data.iter().map(|b| b.to_body("eq")).collect()),
     _=> FlexiBodyPos::Extended(data),
   };
-  thread::sleep(micro_interval);
   let pl_keys = vec!["ma", "me", "ju", "ve", "sa", "ur", "ne", "pl"];
   let station_keys: Vec<&str> = keys.iter().filter(|k| pl_keys.contains(&k.as_str())).map(|k| k.as_str()).collect();
   let planet_stations = if show_planet_stations { match_all_nextprev_planet_stations(date.jd, station_keys, iso_mode) } else{ vec![] };
-  let indian_time = if show_indian_time { Some(to_indian_time(date.jd, geo, offset_secs, iso_mode)) } else { None };
-  Json(json!( ChartDataResult{ valid, date, geo, indian_time, bodies, topo_variants, house, ayanamshas, transitions, progress_items: p2, pheno: pheno_items, planet_stations }))
+  let dasha_levels: u8 = params.dasha.clone().unwrap_or(0);
+  let year_type: String = params.yt.clone().unwrap_or("tropical".to_string());
+  let progression_method = match_progression_method(params.pm.clone().unwrap_or("".to_string()).as_str());
+  let p2: Vec<ProgressItemSet> = if show_p2 { get_bodies_p2(date.jd, geo, p2keys, p2_start_year, p2_years as u16, p2_per_year, year_type.as_str(), progression_method) } else { Vec::new() };
+  let dasha = if dasha_levels > 0 { Some(calc_vimshottari_dasha(date.jd, aya.as_str(), year_type.as_str(), dasha_levels, iso_mode)) } else { None };
+  let indian_time = if show_indian_time { Some(to_indian_time(date.jd, geo, params.to_atmosphere(), offset_secs, iso_mode)?) } else { None };
+  Ok(ChartDataResult{ valid, date, geo, indian_time, bodies, topo_variants, house, ayanamshas, transitions, progress_items: p2, pheno: pheno_items, planet_stations, aspects, dasha, meta, vargas, lots, stars, unknown_stars })
 }
 
 #[get("/progress")]
 async fn bodies_progress(params: Query<InputOptions>) -> impl Responder {
-  reset_ephemeris_path();
+  let _ephemeris = lock_ephemeris();
+  if let Some(error) = params.body_keys_error() {
+    return Json(json!({ "valid": false, "error": error }));
+  }
   let date = to_date_object(&params);
   let loc: String = params.loc.clone().unwrap_or("0,0".to_string());
   let geo = if let Some(geo_pos) = loc_string_to_geo(loc.as_str()) { geo_pos } else { GeoPos::zero() };
@@ -163,7 +277,6 @@ async fn bodies_progress(params: Query<InputOptions>) -> impl Responder {
   let per_day_f64 = if per_day > 0 && per_day < 24 { per_day as f64 } else if day_span > 0 && (day_span as u16) < days { 1f64 / day_span as f64 } else { 2f64 };
   let num_samples = days * per_day_f64 as u16; 
   let days_spanned = if num_samples > 1000 { (1000f64 / per_day_f64) as u16 } else { days };
-  let micro_interval = time::Duration::from_millis(20 + (num_samples / 4) as u64);
   let keys = body_keys_str_to_keys_or(key_string, def_keys);
   let geo_opt = if topo { Some(geo) } else { None };
   let (aya_keys, aya_mode) = to_ayanamsha_keys(&params, "");
@@ -178,7 +291,6 @@ async fn bodies_progress(params: Query<InputOptions>) -> impl Responder {
   let data = calc_bodies_positions_jd(date.jd, to_str_refs(&keys), days_spanned, per_day_f64, geo_opt, eq, iso_mode, aya_offset);
   let frequency = if per_day_f64 < 1f64 { format!("{} days", day_span) } else { format!("{} per day", per_day_f64) };
   let coord_system = build_coord_system_label(eq, topo);
-  thread::sleep(micro_interval);
   Json(json!(json!({ "date": date, "geo": geo, "items": data, "num_samples": num_samples, "days": days, "frequency": frequency, "coordinateSystem": coord_system, "ayanamshas": ayanamshas })))
 }
 
@@ -0,0 +1,17 @@
+use serde_json::*;
+use actix_web::{get, Responder,web::{Query, Json}};
+use super::super::lib::{dasha::*};
+use super::super::{query_params::*, extensions::ephemeris::{lock_ephemeris}};
+
+#[get("/dasha")]
+async fn vimshottari_dasha(params: Query<InputOptions>) -> impl Responder {
+  let _ephemeris = lock_ephemeris();
+  let date = to_date_object(&params);
+  let aya: String = params.aya.clone().unwrap_or("true_citra".to_string());
+  let year_type: String = params.yt.clone().unwrap_or("tropical".to_string());
+  let levels: u8 = params.dasha.clone().unwrap_or(2);
+  let iso_mode: bool = params.iso.clone().unwrap_or(0) > 0;
+  let dasha = calc_vimshottari_dasha(date.jd, aya.as_str(), year_type.as_str(), levels, iso_mode);
+  let valid = dasha.periods.len() > 0;
+  Json(json!({ "valid": valid, "date": date, "dasha": dasha }))
+}
@@ -1,7 +1,7 @@
 use serde_json::*;
 use super::super::lib::{models::{date_info::*, geo_pos::*},transitions::{start_jd_geo, to_indian_time_with_transitions}, utils::{converters::*, validators::*}};
 use actix_web::{get, Responder,web::{Query, Json, Path}};
-use super::super::{query_params::*};
+use super::super::{query_params::*, extensions::ephemeris::{lock_ephemeris}};
 
 #[get("/jd/{dateref}")]
 async fn date_info(dateref: Path<String>) -> impl Responder {
@@ -12,13 +12,17 @@ async fn date_info(dateref: Path<String>) -> impl Responder {
 
 #[get("/date")]
 async fn date_info_geo(params: Query<InputOptions>) -> impl Responder {
+  let _ephemeris = lock_ephemeris();
   let date = to_date_object(&params);
   let loc: String = params.loc.clone().unwrap_or("0,0".to_string());
   let geo = if let Some(geo_pos) = loc_string_to_geo(loc.as_str()) { geo_pos } else { GeoPos::zero() };
   let tz_secs =  params.tzs.clone().unwrap_or(0i16);
   let iso_mode: bool = params.iso.clone().unwrap_or(0) > 0;
   let offset_secs = if tz_secs != 0i16 { Some(tz_secs) } else { None };
-  let (indian, prev, base, next, calc_offset_secs) = to_indian_time_with_transitions(date.jd, geo, offset_secs, iso_mode);
+  let (indian, prev, base, next, calc_offset_secs) = match to_indian_time_with_transitions(date.jd, geo, params.to_atmosphere(), offset_secs, iso_mode) {
+    Ok(result) => result,
+    Err(error) => return Json(json!({ "valid": false, "error": error.to_string() })),
+  };
   Json(json!({ "date": date, "indianTime": indian,  "offsetSecs": calc_offset_secs, "sun": { "prev": prev, "current": base, "next": next } }))
 }
 
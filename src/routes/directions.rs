@@ -0,0 +1,30 @@
+use serde_json::*;
+use actix_web::{get, Responder,web::{Query, Json}};
+use super::super::lib::{directions::*, progressions::{match_progression_method, match_year_type}, aspects::{match_aspect_types, major_aspect_types}, models::{date_info::*, geo_pos::*}, utils::converters::*};
+use super::super::{query_params::*, extensions::ephemeris::{lock_ephemeris}};
+
+#[get("/directions")]
+async fn chart_directions(params: Query<InputOptions>) -> impl Responder {
+  let _ephemeris = lock_ephemeris();
+  if let Some(error) = params.body_keys_error() {
+    return Json(json!({ "valid": false, "error": error }));
+  }
+  let date = to_date_object(&params);
+  let date2 = to_date_object_2(&params);
+  let loc: String = params.loc.clone().unwrap_or("0,0".to_string());
+  let geo = if let Some(geo_pos) = loc_string_to_geo(loc.as_str()) { geo_pos } else { GeoPos::zero() };
+  let def_keys = vec!["su", "mo", "me", "ve", "ma", "ju", "sa"];
+  let key_string: String = params.bodies.clone().unwrap_or("".to_string());
+  let keys = body_keys_str_to_keys_or(key_string, def_keys);
+  let methods = match_direction_methods(params.dm.clone().unwrap_or("".to_string()).as_str());
+  let progression_method = match_progression_method(params.pm.clone().unwrap_or("".to_string()).as_str());
+  let year_type = match_year_type(params.yt.clone().unwrap_or("tropical".to_string()).as_str());
+  let matched_aspect_types = match_aspect_types(params.aspects.clone().unwrap_or("".to_string()).as_str());
+  let aspect_types = if matched_aspect_types.len() > 0 { matched_aspect_types } else { major_aspect_types() };
+  let iso_mode: bool = params.iso.clone().unwrap_or(0) > 0;
+  let span_days = to_span_days(params.days, params.years, 366);
+  let end_date = DateInfo::new_from_jd(date2.jd + span_days);
+  let items = calc_directions(date.jd, geo, to_str_refs(&keys), date2.jd, end_date.jd, &methods, progression_method, year_type, &aspect_types, iso_mode);
+  let valid = items.len() > 0 && date2.jd > date.jd;
+  Json(json!({ "valid": valid, "date": date, "date2": date2, "end": end_date, "geo": geo, "yearType": year_type, "items": items }))
+}
@@ -0,0 +1,22 @@
+use serde_json::*;
+use actix_web::{get, Responder,web::{Query, Json}};
+use super::super::lib::{eclipses::*, models::{date_info::*}, utils::converters::*};
+use super::super::{query_params::*, extensions::ephemeris::{lock_ephemeris}};
+
+#[get("/eclipses")]
+async fn eclipses_range(params: Query<InputOptions>) -> impl Responder {
+  let _ephemeris = lock_ephemeris();
+  let date = to_date_object(&params);
+  let geo_opt = match params.loc.clone() {
+    Some(loc) => loc_string_to_geo(loc.as_str()),
+    _ => None,
+  };
+  let kinds = match_eclipse_kinds(params.ecl.clone().unwrap_or("".to_string()).as_str());
+  let iso_mode: bool = params.iso.clone().unwrap_or(0) > 0;
+  let span_days = to_span_days(params.days, params.years, 366);
+  let end_date = DateInfo::new_from_jd(date.jd + span_days);
+  let items = calc_eclipses_range(date.jd, end_date.jd, &kinds, geo_opt, iso_mode);
+  let previous = calc_previous_eclipses(date.jd, &kinds, geo_opt, iso_mode);
+  let valid = items.len() > 0 || previous.len() > 0;
+  Json(json!({ "valid": valid, "start": date, "end": end_date, "geo": geo_opt, "items": items, "previous": previous }))
+}
@@ -0,0 +1,26 @@
+use serde_json::*;
+use actix_web::{get, Responder,web::{Query, Json}};
+use super::super::lib::{ingresses::*, models::{date_info::*}, utils::converters::*, settings::{ayanamshas::{match_ayanamsha_key}}};
+use super::super::{query_params::*, extensions::ephemeris::{lock_ephemeris}};
+
+#[get("/ingresses")]
+async fn body_ingresses(params: Query<InputOptions>) -> impl Responder {
+  let _ephemeris = lock_ephemeris();
+  if let Some(error) = params.body_keys_error() {
+    return Json(json!({ "valid": false, "error": error }));
+  }
+  let date = to_date_object(&params);
+  let def_keys = vec!["su", "mo", "me", "ve", "ma", "ju", "sa"];
+  let key_string: String = params.bodies.clone().unwrap_or("".to_string());
+  let keys = body_keys_str_to_keys_or(key_string, def_keys);
+  let mode = match_ingress_mode(params.ingress.clone().unwrap_or("".to_string()).as_str());
+  let sidereal: bool = params.sid.unwrap_or(0) > 0;
+  let aya: String = params.aya.clone().unwrap_or("true_citra".to_string());
+  let aya_key = if sidereal { match_ayanamsha_key(aya.as_str()) } else { "tropical".to_string() };
+  let iso_mode: bool = params.iso.clone().unwrap_or(0) > 0;
+  let span_days = to_span_days(params.days, params.years, 366);
+  let end_date = DateInfo::new_from_jd(date.jd + span_days);
+  let items = calc_ingresses(to_str_refs(&keys), &mode, aya_key.as_str(), date.jd, end_date.jd, iso_mode);
+  let valid = keys.len() > 0;
+  Json(json!({ "valid": valid, "start": date, "end": end_date, "mode": mode.as_key(), "ayanamsha": aya_key, "items": items }))
+}
@@ -0,0 +1,58 @@
+use serde_json::*;
+use actix_web::{get, Responder,web::{Query, Json}};
+use super::super::lib::{midpoints::*, harmonics::*, core::{get_ayanamsha_value}, aspects::{calc_chart_aspect_positions}, math_funcs::{subtract_360}, models::{geo_pos::*, graha_pos::*}, utils::converters::*};
+use super::super::{query_params::*, extensions::ephemeris::{lock_ephemeris}};
+
+/*
+* Tropical or sidereal ecliptic positions of the bodies with the ascendant and MC as in /chart-data
+*/
+fn to_chart_point_positions(params: &Query<InputOptions>, jd: f64, geo: GeoPos) -> (Vec<GrahaPos>, f64) {
+  let def_keys = vec!["su", "mo", "ma", "me", "ju", "ve", "sa", "ur", "ne", "pl"];
+  let key_string: String = params.bodies.clone().unwrap_or("".to_string());
+  let keys = body_keys_str_to_keys_or(key_string, def_keys);
+  let topo: bool = params.topo.clone().unwrap_or(0) > 0;
+  let aya: String = params.aya.clone().unwrap_or("true_citra".to_string());
+  let sidereal: bool = params.sid.unwrap_or(0) > 0;
+  let aya_offset = if sidereal { get_ayanamsha_value(jd, aya.as_str()) } else { 0f64 };
+  let mut positions = calc_chart_aspect_positions(jd, geo, &keys, topo);
+  for pos in positions.iter_mut() {
+    pos.lng = subtract_360(pos.lng, aya_offset);
+  }
+  (positions, aya_offset)
+}
+
+#[get("/midpoints")]
+async fn chart_midpoints(params: Query<InputOptions>) -> impl Responder {
+  let _ephemeris = lock_ephemeris();
+  if let Some(error) = params.body_keys_error() {
+    return Json(json!({ "valid": false, "error": error }));
+  }
+  let date = to_date_object(&params);
+  let loc: String = params.loc.clone().unwrap_or("0,0".to_string());
+  let geo = if let Some(geo_pos) = loc_string_to_geo(loc.as_str()) { geo_pos } else { GeoPos::zero() };
+  let dial: f64 = match params.dial.unwrap_or(90f64) { d if d >= 1f64 && d <= 360f64 => d, _ => 90f64 };
+  let orb: f64 = params.orb.unwrap_or(DEFAULT_MIDPOINT_ORB);
+  let (positions, aya_offset) = to_chart_point_positions(&params, date.jd, geo);
+  let midpoints = calc_midpoints(&positions, dial, orb);
+  let trees = to_midpoint_trees(&positions, &midpoints, dial);
+  let valid = positions.len() > 0;
+  Json(json!({ "valid": valid, "date": date, "geo": geo, "dial": dial, "orb": orb, "ayanamsha": aya_offset, "positions": positions.iter().map(|p| p.to_body("ecl")).collect::<Vec<BodyPos>>(), "midpoints": midpoints, "trees": trees }))
+}
+
+#[get("/harmonics")]
+async fn chart_harmonics(params: Query<InputOptions>) -> impl Responder {
+  let _ephemeris = lock_ephemeris();
+  if let Some(error) = params.body_keys_error() {
+    return Json(json!({ "valid": false, "error": error }));
+  }
+  let date = to_date_object(&params);
+  let loc: String = params.loc.clone().unwrap_or("0,0".to_string());
+  let geo = if let Some(geo_pos) = loc_string_to_geo(loc.as_str()) { geo_pos } else { GeoPos::zero() };
+  let matched_nums = match_harmonic_nums(params.harmonics.clone().unwrap_or("".to_string()).as_str());
+  let nums = if matched_nums.len() > 0 { matched_nums } else { vec![5, 7, 9] };
+  let (positions, aya_offset) = to_chart_point_positions(&params, date.jd, geo);
+  let lng_items: Vec<(String, f64)> = positions.iter().map(|p| (p.key.clone(), p.lng)).collect();
+  let items = to_harmonic_sets(&lng_items, 0f64, &nums);
+  let valid = items.len() > 0;
+  Json(json!({ "valid": valid, "date": date, "geo": geo, "ayanamsha": aya_offset, "items": items }))
+}
@@ -2,4 +2,16 @@ pub mod chart_data;
 pub mod transitions;
 pub mod datetime;
 pub mod planet_stations;
-pub mod progress_synastry;
\ No newline at end of file
+pub mod progress_synastry;
+pub mod aspects;
+pub mod dasha;
+pub mod panchanga;
+pub mod eclipses;
+pub mod moon_phases;
+pub mod ingresses;
+pub mod directions;
+pub mod returns;
+pub mod relationships;
+pub mod midpoints;
+pub mod stars;
+pub mod sky;
\ No newline at end of file
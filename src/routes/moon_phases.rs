@@ -0,0 +1,17 @@
+use serde_json::*;
+use actix_web::{get, Responder,web::{Query, Json}};
+use super::super::lib::{moon_phases::*, models::{date_info::*}};
+use super::super::{query_params::*, extensions::ephemeris::{lock_ephemeris}};
+
+#[get("/moon-phases")]
+async fn moon_phases_range(params: Query<InputOptions>) -> impl Responder {
+  let _ephemeris = lock_ephemeris();
+  let date = to_date_object(&params);
+  let iso_mode: bool = params.iso.clone().unwrap_or(0) > 0;
+  let span_days = to_span_days(params.days, params.years, 366);
+  let end_date = DateInfo::new_from_jd(date.jd + span_days);
+  let current = calc_moon_phase_state(date.jd);
+  let items = calc_moon_phases_range(date.jd, end_date.jd, iso_mode);
+  let valid = items.len() > 0;
+  Json(json!({ "valid": valid, "start": date, "end": end_date, "current": current, "items": items }))
+}
@@ -0,0 +1,22 @@
+use serde_json::*;
+use actix_web::{get, Responder,web::{Query, Json}};
+use super::super::lib::{panchanga::*, models::geo_pos::*, utils::converters::*};
+use super::super::{query_params::*, extensions::ephemeris::{lock_ephemeris}};
+
+#[get("/panchanga")]
+async fn panchanga_data(params: Query<InputOptions>) -> impl Responder {
+  let _ephemeris = lock_ephemeris();
+  let date = to_date_object(&params);
+  let loc: String = params.loc.clone().unwrap_or("0,0".to_string());
+  let geo = if let Some(geo_pos) = loc_string_to_geo(loc.as_str()) { geo_pos } else { GeoPos::zero() };
+  let aya: String = params.aya.clone().unwrap_or("true_citra".to_string());
+  let iso_mode: bool = params.iso.clone().unwrap_or(0) > 0;
+  let tz_secs = params.tzs.clone().unwrap_or(-1);
+  let offset_secs = if tz_secs == -1 { None } else { Some(tz_secs) };
+  let panchanga = match calc_panchanga(date.jd, geo, params.to_atmosphere(), aya.as_str(), offset_secs, iso_mode) {
+    Ok(panchanga) => panchanga,
+    Err(error) => return Json(json!({ "valid": false, "error": error.to_string() })),
+  };
+  let valid = panchanga.day_end > panchanga.day_start;
+  Json(json!({ "valid": valid, "date": date, "geo": geo, "panchanga": panchanga }))
+}
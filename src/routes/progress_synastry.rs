@@ -1,26 +1,67 @@
-use std::{thread, time};
 use serde_json::*;
 use actix_web::{get, Responder,web::{Query, Json} };
-use super::super::lib::{julian_date::{current_year}, core::*,  models::{geo_pos::*, general::*}, utils::{converters::*}};
-use super::super::{reset_ephemeris_path, query_params::*};
+use super::super::lib::{julian_date::{current_year}, core::*, math_funcs::{to_progression_jd}, progressions::*, models::{geo_pos::*, graha_pos::*, houses::{match_house_systems_chars}, general::*}, utils::{converters::*}};
+use super::super::{query_params::*, extensions::ephemeris::{lock_ephemeris}};
 
 #[get("/p2")]
 async fn progress_synastry_items(params: Query<InputOptions>) -> impl Responder {
-  reset_ephemeris_path();
-  let micro_interval = time::Duration::from_millis(30);  
+  let _ephemeris = lock_ephemeris();
+  if let Some(error) = params.body_keys_error() {
+    return Json(json!({ "valid": false, "error": error }));
+  }
   let loc: String = params.loc.clone().unwrap_or("0,0".to_string());
   let geo = if let Some(geo_pos) = loc_string_to_geo(loc.as_str()) { geo_pos } else { GeoPos::zero() };
   let date = to_date_object(&params);
+  let date2 = to_date_object_2(&params);
+  let topo: u8 = params.topo.clone().unwrap_or(0);
+  let eq: u8 = params.eq.clone().unwrap_or(2); // 0 ecliptic, 1 equatorial, 2 both
+  let show_pheno_inline = eq == 4;
+  let iso_mode: bool = params.iso.clone().unwrap_or(0) > 0;
+  let year_type: String = params.yt.clone().unwrap_or("tropical".to_string());
+  let method = match_progression_method(params.pm.clone().unwrap_or("".to_string()).as_str());
+  let hsys_str = params.hsys.clone().unwrap_or("W".to_string());
+  let h_systems: Vec<char> = if hsys_str.to_lowercase().as_str() == "all" { vec!['a'] } else { match_house_systems_chars(hsys_str) };
+  let def_keys = vec!["su", "mo", "ma", "me", "ju", "ve", "sa"];
+  let key_string: String = params.bodies.clone().unwrap_or("".to_string());
+  let keys = body_keys_str_to_keys_or(key_string, def_keys.clone());
+  let aya: String = params.aya.clone().unwrap_or("true_citra".to_string());
+  let sidereal: bool = params.sid.unwrap_or(0) > 0;
+  let pd = to_progression_jd(date.jd, date2.jd, match_year_type(year_type.as_str()));
+  let ayanamsha = get_ayanamsha_value(pd, aya.as_str());
+  let aya_offset = if sidereal { ayanamsha } else { 0f64 };
+  let aya_offset_val = match eq {
+    1 => 0f64,
+    _ => aya_offset
+  };
+  let progressed = calc_progressed_chart(date.jd, date2.jd, geo, method, year_type.as_str(), h_systems, aya_offset_val, iso_mode);
+  let data = match topo {
+    1 => match eq {
+      0 => get_bodies_ecl_topo(pd, to_str_refs(&keys), geo, aya_offset),
+      1 => get_bodies_eq_topo(pd, to_str_refs(&keys), geo),
+      _ => get_bodies_dual_topo(pd, to_str_refs(&keys), geo, show_pheno_inline, aya_offset),
+    }
+    _ => match eq {
+      0 => get_bodies_ecl_geo(pd, to_str_refs(&keys), aya_offset),
+      1 => get_bodies_eq_geo(pd, to_str_refs(&keys)),
+      _ => get_bodies_dual_geo(pd, to_str_refs(&keys), show_pheno_inline, aya_offset),
+    }
+  };
+  let valid = data.len() > 0;
+  let bodies: FlexiBodyPos = match eq {
+    0 => FlexiBodyPos::Simple(data.iter().map(|b| b.to_body("ecl")).collect()),
+    1 => FlexiBodyPos::Simple(data.iter().map(|b| b.to_body("eq")).collect()),
+    _=> FlexiBodyPos::Extended(data),
+  };
+  let show_p2: bool = params.p2.clone().unwrap_or(1) > 0;
   let p2_ago: u8 = params.p2ago.clone().unwrap_or(1);
   let p2_start: u16 = params.p2start.clone().unwrap_or(0);
   let p2_start_year = if p2_start > 1800 { p2_start as u32 } else { current_year() as u32 - p2_ago as u32 };
   let p2_years: u8 = params.p2yrs.clone().unwrap_or(3);
   let p2_per_year: u8 = params.p2py.clone().unwrap_or(2);
-  let def_keys = vec!["su", "mo", "ma", "me", "ju", "ve", "sa"];
-  let key_string: String = params.bodies.clone().unwrap_or("".to_string());
-  let keys = body_keys_str_to_keys_or(key_string, def_keys);
-  let items: Vec<ProgressItemSet> = get_bodies_p2(date.jd, keys, p2_start_year, p2_years as u16, p2_per_year);
-  let valid = items.len() > 0;
-  thread::sleep(micro_interval);
-  Json(json!({ "valid": valid, "date": date,  "start_year": p2_start_year, "years": p2_years, "per_year": p2_per_year, "geo": geo, "items": items }))
+  let available_p2_keys = vec!["as", "su", "mo", "ma", "me", "ju", "ve", "sa"];
+  let p2_key_string: String = params.p2bodies.clone().unwrap_or(params.bodies.clone().unwrap_or("".to_string()));
+  let p2_matched_keys: Vec<String> = body_keys_str_to_keys(p2_key_string).into_iter().filter(|k| available_p2_keys.contains(&k.as_str())).collect();
+  let p2keys: Vec<String> = if p2_matched_keys.len() > 0 { p2_matched_keys } else { def_keys.into_iter().map(|k| k.to_string()).collect() };
+  let items: Vec<ProgressItemSet> = if show_p2 { get_bodies_p2(date.jd, geo, p2keys, p2_start_year, p2_years as u16, p2_per_year, progressed.year_type.as_str(), method) } else { vec![] };
+  Json(json!({ "valid": valid, "date": date, "date2": date2, "start_year": p2_start_year, "years": p2_years, "per_year": p2_per_year, "geo": geo, "progressed": progressed, "bodies": bodies, "ayanamsha": { "key": aya, "value": ayanamsha, "applied": sidereal }, "items": items }))
 }
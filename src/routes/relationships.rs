@@ -0,0 +1,52 @@
+use serde_json::*;
+use actix_web::{get, Responder,web::{Query, Json}};
+use super::super::lib::{composite::*, core::{get_ayanamsha_value}, math_funcs::{mid_point_to_surface}, models::{date_info::*, geo_pos::*, houses::{match_house_systems_chars}}, utils::converters::*};
+use super::super::{query_params::*, extensions::ephemeris::{lock_ephemeris}};
+use super::chart_data::{build_chart_data};
+
+#[get("/composite")]
+async fn composite_chart(params: Query<InputOptions>) -> impl Responder {
+  let _ephemeris = lock_ephemeris();
+  if let Some(error) = params.body_keys_error() {
+    return Json(json!({ "valid": false, "error": error }));
+  }
+  let date = to_date_object(&params);
+  let date2 = to_date_object_2(&params);
+  let loc: String = params.loc.clone().unwrap_or("0,0".to_string());
+  let geo = if let Some(geo_pos) = loc_string_to_geo(loc.as_str()) { geo_pos } else { GeoPos::zero() };
+  let loc2: String = params.loc2.clone().unwrap_or("0,0".to_string());
+  let geo2 = if let Some(geo_pos) = loc_string_to_geo(loc2.as_str()) { geo_pos } else { GeoPos::zero() };
+  let def_keys = vec!["su", "mo", "ma", "me", "ju", "ve", "sa", "ur", "ne", "pl"];
+  let key_string: String = params.bodies.clone().unwrap_or("".to_string());
+  let keys = body_keys_str_to_keys_or(key_string, def_keys);
+  let hsys_str = params.hsys.clone().unwrap_or("W".to_string());
+  let h_systems: Vec<char> = if hsys_str.to_lowercase().as_str() == "all" { vec!['a'] } else { match_house_systems_chars(hsys_str) };
+  let aya: String = params.aya.clone().unwrap_or("true_citra".to_string());
+  let sidereal: bool = params.sid.unwrap_or(0) > 0;
+  let ayanamsha = get_ayanamsha_value((date.jd + date2.jd) / 2f64, aya.as_str());
+  let aya_offset = if sidereal { ayanamsha } else { 0f64 };
+  let composite = calc_composite_chart(date.jd, geo, date2.jd, geo2, to_str_refs(&keys), h_systems, aya_offset);
+  let valid = composite.bodies.len() > 0;
+  Json(json!({ "valid": valid, "date": date, "geo": geo, "date2": date2, "geo2": geo2, "ayanamsha": { "key": aya, "value": ayanamsha, "applied": sidereal }, "composite": composite }))
+}
+
+#[get("/davison")]
+async fn davison_chart(params: Query<InputOptions>) -> impl Responder {
+  let _ephemeris = lock_ephemeris();
+  if let Some(error) = params.body_keys_error() {
+    return Json(json!({ "valid": false, "error": error }));
+  }
+  let date = to_date_object(&params);
+  let date2 = to_date_object_2(&params);
+  let loc: String = params.loc.clone().unwrap_or("0,0".to_string());
+  let geo = if let Some(geo_pos) = loc_string_to_geo(loc.as_str()) { geo_pos } else { GeoPos::zero() };
+  let loc2: String = params.loc2.clone().unwrap_or("0,0".to_string());
+  let geo2 = if let Some(geo_pos) = loc_string_to_geo(loc2.as_str()) { geo_pos } else { GeoPos::zero() };
+  let mid_geo = mid_point_to_surface(geo, geo2);
+  let mid_date = DateInfo::new_from_jd((date.jd + date2.jd) / 2f64);
+  let chart = match build_chart_data(mid_date, mid_geo, &params, &vec![]) {
+    Ok(chart) => chart,
+    Err(error) => return Json(json!({ "valid": false, "error": error.to_string() })),
+  };
+  Json(json!({ "valid": chart.valid, "date": date, "geo": geo, "date2": date2, "geo2": geo2, "chart": chart }))
+}
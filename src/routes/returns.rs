@@ -0,0 +1,56 @@
+use serde_json::*;
+use actix_web::{get, Responder,web::{Query, Json}};
+use super::super::lib::{returns::*, core::{match_numbered_asteroid_error}, julian_date::{julian_day_to_iso_datetime}, models::{date_info::*, geo_pos::*}, utils::converters::*, settings::{ayanamshas::{match_ayanamsha_key}, graha_values::{to_unknown_body_keys_error}}};
+use super::super::{query_params::*, extensions::ephemeris::{lock_ephemeris}};
+use serde::{Serialize, Deserialize};
+use super::chart_data::{build_chart_data, ChartDataResult};
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct ReturnItem {
+  jd: f64,
+  #[serde(skip_serializing_if = "String::is_empty")]
+  dt: String,
+  chart: ChartDataResult,
+}
+
+#[get("/returns")]
+async fn body_returns(params: Query<InputOptions>) -> impl Responder {
+  let _ephemeris = lock_ephemeris();
+  if let Some(error) = params.body_keys_error() {
+    return Json(json!({ "valid": false, "error": error }));
+  }
+  let date = to_date_object(&params);
+  let natal_date = to_date_object_2(&params);
+  let loc2: String = params.loc2.clone().unwrap_or("0,0".to_string());
+  let natal_geo = if let Some(geo_pos) = loc_string_to_geo(loc2.as_str()) { geo_pos } else { GeoPos::zero() };
+  let geo = match params.loc.clone() {
+    Some(loc) => if let Some(geo_pos) = loc_string_to_geo(loc.as_str()) { geo_pos } else { natal_geo },
+    _ => natal_geo,
+  };
+  let return_type = params.return_type.clone().unwrap_or("solar".to_string());
+  let key = match match_return_body(return_type.as_str()) {
+    Some(key) => key,
+    _ => return Json(json!({ "valid": false, "error": to_unknown_body_keys_error(&vec![return_type]) })),
+  };
+  let sidereal: bool = params.sid.unwrap_or(0) > 0;
+  let aya: String = params.aya.clone().unwrap_or("true_citra".to_string());
+  let aya_key = if sidereal { match_ayanamsha_key(aya.as_str()) } else { "tropical".to_string() };
+  let iso_mode: bool = params.iso.clone().unwrap_or(0) > 0;
+  let (start_jd, end_jd) = match params.year {
+    Some(year) => calendar_period_jds(year, params.month.unwrap_or(0)),
+    _ => (date.jd, date.jd + to_span_days(params.days, params.years, return_search_days(key.as_str()))),
+  };
+  if let Some(error) = match_numbered_asteroid_error(natal_date.jd, &[key.clone()]).or_else(|| match_numbered_asteroid_error(start_jd, &[key.clone()])) {
+    return Json(json!({ "valid": false, "error": error }));
+  }
+  let (natal_lng, jds) = calc_returns(natal_date.jd, key.as_str(), sidereal, aya.as_str(), start_jd, end_jd);
+  let items: Vec<ReturnItem> = match jds.into_iter().map(|jd| {
+    let dt = if iso_mode { julian_day_to_iso_datetime(jd) } else { "".to_string() };
+    build_chart_data(DateInfo::new_from_jd(jd), geo, &params, &vec![]).map(|chart| ReturnItem { jd, dt, chart })
+  }).collect() {
+    Ok(items) => items,
+    Err(error) => return Json(json!({ "valid": false, "error": error.to_string() })),
+  };
+  let valid = items.len() > 0;
+  Json(json!({ "valid": valid, "key": key, "natalDate": natal_date, "natalGeo": natal_geo, "natalLng": natal_lng, "ayanamsha": aya_key, "start": DateInfo::new_from_jd(start_jd), "end": DateInfo::new_from_jd(end_jd), "geo": geo, "items": items }))
+}
@@ -0,0 +1,25 @@
+use serde_json::*;
+use actix_web::{get, Responder,web::{Query, Json}};
+use super::super::lib::{sky::*, core::{match_calc_error}, models::geo_pos::*, utils::converters::*};
+use super::super::{query_params::*, extensions::ephemeris::{lock_ephemeris}};
+
+#[get("/sky")]
+async fn sky_bodies(params: Query<InputOptions>) -> impl Responder {
+  let _ephemeris = lock_ephemeris();
+  if let Some(error) = params.body_keys_error() {
+    return Json(json!({ "valid": false, "error": error }));
+  }
+  let date = to_date_object(&params);
+  let loc: String = params.loc.clone().unwrap_or("0,0".to_string());
+  let geo = if let Some(geo_pos) = loc_string_to_geo(loc.as_str()) { geo_pos } else { GeoPos::zero() };
+  let def_keys = vec!["su", "mo", "me", "ve", "ma", "ju", "sa", "ur", "ne", "pl"];
+  let key_string: String = params.bodies.clone().unwrap_or("".to_string());
+  let keys = body_keys_str_to_keys_or(key_string, def_keys);
+  if let Some(error) = match_calc_error(date.jd, &keys) {
+    return Json(json!({ "valid": false, "error": error }));
+  }
+  let atmosphere = params.to_atmosphere();
+  let bodies = get_visible_bodies(date.jd, to_str_refs(&keys), geo, atmosphere);
+  let below: Vec<String> = keys.into_iter().filter(|key| !bodies.iter().any(|pos| pos.key == *key)).collect();
+  Json(json!({ "valid": true, "date": date, "geo": geo, "horizon": atmosphere.horizon, "bodies": bodies, "below": below }))
+}
@@ -0,0 +1,40 @@
+use serde_json::*;
+use actix_web::{get, Responder,web::{Query, Json}};
+use super::super::lib::{fixed_stars::*, core::{get_ayanamsha_value}, models::{geo_pos::*, general::*}, utils::converters::*};
+use super::super::{query_params::*, extensions::ephemeris::{lock_ephemeris}};
+
+#[get("/stars")]
+async fn fixed_stars(params: Query<InputOptions>) -> impl Responder {
+  let _ephemeris = lock_ephemeris();
+  if let Some(error) = params.body_keys_error() {
+    return Json(json!({ "valid": false, "error": error }));
+  }
+  let date = to_date_object(&params);
+  let loc: String = params.loc.clone().unwrap_or("0,0".to_string());
+  let geo = if let Some(geo_pos) = loc_string_to_geo(loc.as_str()) { geo_pos } else { GeoPos::zero() };
+  let matched_names = match_star_names(params.stars.clone().unwrap_or("".to_string()).as_str());
+  let star_names = if matched_names.len() > 0 { matched_names } else { match_star_names("all") };
+  let aya: String = params.aya.clone().unwrap_or("true_citra".to_string());
+  let sidereal: bool = params.sid.unwrap_or(0) > 0;
+  let aya_offset = if sidereal { get_ayanamsha_value(date.jd, aya.as_str()) } else { 0f64 };
+  let iso_mode: bool = params.iso.clone().unwrap_or(0) > 0;
+  let show_transitions: bool = params.ct.clone().unwrap_or(1) > 0;
+  let show_parans: bool = params.parans.clone().unwrap_or(1) > 0;
+  let def_keys = vec!["su", "mo", "ma", "me", "ju", "ve", "sa"];
+  let key_string: String = params.bodies.clone().unwrap_or("".to_string());
+  let keys = body_keys_str_to_keys_or(key_string, def_keys);
+  let orb: f64 = params.orb.unwrap_or(DEFAULT_PARAN_ORB);
+  let (stars, unknown_stars) = calc_star_positions(date.jd, &star_names, aya_offset);
+  let matched_star_names: Vec<String> = stars.iter().map(|s| s.key.clone()).collect();
+  let transition_sets = if show_transitions { get_star_transition_sets(date.jd, &matched_star_names, geo) } else { Ok(vec![]) };
+  let transitions: Vec<KeyFlexiValueSet> = match transition_sets {
+    Ok(sets) => sets.iter().map(|item| item.as_flexi_values(iso_mode)).collect(),
+    Err(error) => return Json(json!({ "valid": false, "error": error.to_string() })),
+  };
+  let parans = match if show_parans { calc_parans(date.jd, &matched_star_names, &keys, geo, orb, iso_mode) } else { Ok(vec![]) } {
+    Ok(parans) => parans,
+    Err(error) => return Json(json!({ "valid": false, "error": error.to_string() })),
+  };
+  let valid = stars.len() > 0;
+  Json(json!({ "valid": valid, "date": date, "geo": geo, "ayanamsha": aya_offset, "stars": stars, "unknownStars": unknown_stars, "transitions": transitions, "parans": parans, "orb": orb }))
+}